@@ -0,0 +1,94 @@
+//! Strategies for choosing which column to branch on.
+//!
+//! Branching on a column tries every row that covers it, so the choice of
+//! column determines the shape and size of the search tree, though never
+//! the set of solutions found.
+
+use core::cmp::Ordering;
+
+use crate::matrix::Col;
+
+pub trait Heuristic {
+    /// Choose a column to branch on from the uncovered primary columns,
+    /// given alongside the number of rows that can still cover them.
+    ///
+    /// Called only when there is at least one candidate. Returning `None`
+    /// prunes the current branch.
+    fn choose<I: Iterator<Item = (Col, u32)>>(&self, candidates: I) -> Option<Col>;
+}
+
+/// Branch on the column with the fewest remaining rows, preferring the
+/// leftmost on ties.
+///
+/// This is Knuth's recommended default: it fails fast on columns that
+/// can't be covered, and keeps the branching factor low near the root.
+/// Finding the minimum costs a scan over all uncovered columns per node,
+/// cut short by any column with no remaining rows.
+///
+/// Tracking sizes incrementally in buckets, as Knuth suggests, would avoid
+/// the scan at the cost of more work on every size update during cover and
+/// uncover, which matters little with the few dozen columns of a pentomino
+/// rectangle.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct MinRemaining;
+
+impl Heuristic for MinRemaining {
+    fn choose<I: Iterator<Item = (Col, u32)>>(&self, candidates: I) -> Option<Col> {
+        let mut best = None::<(Col, u32)>;
+
+        for (col, size) in candidates {
+            // No remaining row can cover this column, so prune immediately
+            if size == 0 {
+                return None;
+            }
+
+            if best.is_none_or(|(_, min)| size < min) {
+                best = Some((col, size));
+            }
+        }
+
+        best.map(|(col, _)| col)
+    }
+}
+
+/// Branch on the column with the fewest remaining rows, like [`MinRemaining`],
+/// but break ties with the comparator `self.0` instead of preferring the
+/// leftmost, choosing the least column under the comparator.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct MinRemainingBy<F>(pub F);
+
+impl<F: Fn(Col, Col) -> Ordering> Heuristic for MinRemainingBy<F> {
+    fn choose<I: Iterator<Item = (Col, u32)>>(&self, candidates: I) -> Option<Col> {
+        let mut best = None::<(Col, u32)>;
+
+        for (col, size) in candidates {
+            if size == 0 {
+                return None;
+            }
+
+            let better = best.is_none_or(|(min_col, min)| {
+                size.cmp(&min).then_with(|| (self.0)(col, min_col)) == Ordering::Less
+            });
+
+            if better {
+                best = Some((col, size));
+            }
+        }
+
+        best.map(|(col, _)| col)
+    }
+}
+
+/// Branch on the leftmost uncovered column.
+///
+/// Choosing is constant time, but the search tree can be exponentially
+/// larger than with [`MinRemaining`], since a column with no remaining rows
+/// isn't noticed until it happens to be leftmost.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct FirstColumn;
+
+impl Heuristic for FirstColumn {
+    fn choose<I: Iterator<Item = (Col, u32)>>(&self, mut candidates: I) -> Option<Col> {
+        candidates.next().map(|(col, _)| col)
+    }
+}
@@ -0,0 +1,282 @@
+//! Polyhexes, the hexagonal analogue of [`Tile`](crate::Tile).
+//!
+//! Cells are addressed by axial coordinates `(q, r)`, where the six
+//! neighbors of `(q, r)` are `(q ± 1, r)`, `(q, r ± 1)`, `(q + 1, r - 1)`,
+//! and `(q - 1, r + 1)`.
+
+use core::cmp::Ordering;
+
+use alloc::vec::Vec;
+
+// Invariant: `self.0` is sorted.
+#[derive(Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HexSet<const LEN: usize>(Vec<HexTile<LEN>>);
+
+impl<const LEN: usize> HexSet<LEN> {
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn push(&mut self, tile: HexTile<LEN>) {
+        self.0.push(tile);
+        self.0.sort();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &HexTile<LEN>> {
+        self.0.iter()
+    }
+
+    pub fn rotate_60(&self) -> Self {
+        self.transform_clamp(SHexPoint::rotate_60)
+    }
+
+    pub fn reflect(&self) -> Self {
+        self.transform_clamp(SHexPoint::reflect)
+    }
+
+    /// Return the least of the twelve rotations and reflections of this set,
+    /// so sets equal up to symmetry canonicalize to the same value.
+    pub fn canonicalize(&self) -> Self {
+        (0..12)
+            .map(|symmetry| self.transform_clamp(|point| point.symmetry(symmetry)))
+            .min()
+            .unwrap_or_default()
+    }
+
+    fn transform_clamp<F: Fn(&SHexPoint) -> SHexPoint>(&self, apply: F) -> Self {
+        let tiles = self
+            .0
+            .iter()
+            .map(|tile| tile.transform(&apply))
+            .collect::<Vec<_>>();
+
+        let min_q = tiles
+            .iter()
+            .flatten()
+            .map(|point| point.q)
+            .min()
+            .unwrap_or(0);
+        let min_r = tiles
+            .iter()
+            .flatten()
+            .map(|point| point.r)
+            .min()
+            .unwrap_or(0);
+
+        tiles
+            .iter()
+            .map(|tile| {
+                core::array::from_fn(|index| HexPoint::from(tile[index].translate(-min_q, -min_r)))
+            })
+            .map(HexTile::new)
+            .collect()
+    }
+}
+
+impl<const LEN: usize> FromIterator<HexTile<LEN>> for HexSet<LEN> {
+    fn from_iter<T: IntoIterator<Item = HexTile<LEN>>>(iter: T) -> Self {
+        let mut tiles = Vec::from_iter(iter);
+        tiles.sort();
+        Self(tiles)
+    }
+}
+
+// Invariant: `self.0` is sorted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HexTile<const LEN: usize>([HexPoint; LEN]);
+
+impl<const LEN: usize> HexTile<LEN> {
+    pub const fn new(mut points: [HexPoint; LEN]) -> Self {
+        // Manual bubble sort to preserve `const` compatibility :(
+        'outer: loop {
+            let swap;
+            let mut i = 0;
+
+            while i + 1 < points.len() {
+                match points[i].cmp(&points[i + 1]) {
+                    Ordering::Less | Ordering::Equal => i += 1,
+                    Ordering::Greater => {
+                        swap = points[i];
+                        points[i] = points[i + 1];
+                        points[i + 1] = swap;
+                        continue 'outer;
+                    }
+                }
+            }
+
+            break;
+        }
+
+        Self(points)
+    }
+
+    /// Return the distinct rotations of this tile, excluding reflections.
+    ///
+    /// There are at most six, one for each multiple of 60 degrees.
+    pub fn rotations(&self) -> impl Iterator<Item = Self> {
+        self.orientations(0..6)
+    }
+
+    /// Return the distinct rotations and reflections of this tile.
+    ///
+    /// There are at most twelve, one for each symmetry of the hexagon.
+    pub fn transformations(&self) -> impl Iterator<Item = Self> {
+        self.orientations(0..12)
+    }
+
+    pub fn rotate_60(&self) -> Self {
+        self.transform_clamp(SHexPoint::rotate_60)
+    }
+
+    pub fn reflect(&self) -> Self {
+        self.transform_clamp(SHexPoint::reflect)
+    }
+
+    fn orientations(&self, symmetries: core::ops::Range<usize>) -> impl Iterator<Item = Self> {
+        let mut orientations = Vec::with_capacity(symmetries.len());
+
+        for symmetry in symmetries {
+            let tile = self.transform_clamp(|point| point.symmetry(symmetry));
+            if !orientations.contains(&tile) {
+                orientations.push(tile);
+            }
+        }
+
+        orientations.into_iter()
+    }
+
+    fn transform<F: Fn(&SHexPoint) -> SHexPoint>(&self, apply: F) -> [SHexPoint; LEN] {
+        core::array::from_fn(|index| apply(&SHexPoint::from(self.0[index])))
+    }
+
+    fn transform_clamp<F: Fn(&SHexPoint) -> SHexPoint>(&self, apply: F) -> Self {
+        let tile = self.transform(apply);
+        let min_q = tile.iter().map(|point| point.q).min().unwrap_or(0);
+        let min_r = tile.iter().map(|point| point.r).min().unwrap_or(0);
+        let mut tile =
+            core::array::from_fn(|index| HexPoint::from(tile[index].translate(-min_q, -min_r)));
+        tile.sort();
+        Self(tile)
+    }
+}
+
+impl<const LEN: usize> AsRef<[HexPoint; LEN]> for HexTile<LEN> {
+    fn as_ref(&self) -> &[HexPoint; LEN] {
+        &self.0
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HexPoint {
+    pub q: u16,
+    pub r: u16,
+}
+
+impl HexPoint {
+    const fn cmp(&self, other: &Self) -> Ordering {
+        if self.q > other.q {
+            return Ordering::Greater;
+        } else if self.q < other.q {
+            return Ordering::Less;
+        }
+
+        if self.r > other.r {
+            return Ordering::Greater;
+        } else if self.r < other.r {
+            return Ordering::Less;
+        }
+
+        Ordering::Equal
+    }
+}
+
+// Intermediate representation to simplify hex transformations.
+#[derive(Copy, Clone, Debug)]
+struct SHexPoint {
+    q: i32,
+    r: i32,
+}
+
+impl SHexPoint {
+    fn translate(&self, dq: i32, dr: i32) -> Self {
+        Self {
+            q: self.q + dq,
+            r: self.r + dr,
+        }
+    }
+
+    // Symmetries 0 through 5 rotate by multiples of 60 degrees, and
+    // 6 through 11 reflect before rotating.
+    fn symmetry(&self, symmetry: usize) -> Self {
+        let point = match symmetry < 6 {
+            true => *self,
+            false => self.reflect(),
+        };
+        (0..symmetry % 6).fold(point, |point, _| point.rotate_60())
+    }
+
+    // In cube coordinates `(q, -q - r, r)`, rotation by 60 degrees
+    // cycles and negates the components.
+    fn rotate_60(&self) -> Self {
+        Self {
+            q: -self.r,
+            r: self.q + self.r,
+        }
+    }
+
+    // Swapping `q` and `r` reflects across the line `q = r`.
+    fn reflect(&self) -> Self {
+        Self {
+            q: self.r,
+            r: self.q,
+        }
+    }
+}
+
+impl From<HexPoint> for SHexPoint {
+    fn from(HexPoint { q, r }: HexPoint) -> Self {
+        Self {
+            q: q as _,
+            r: r as _,
+        }
+    }
+}
+
+impl From<SHexPoint> for HexPoint {
+    fn from(SHexPoint { q, r }: SHexPoint) -> Self {
+        Self {
+            q: u16::try_from(q).expect("Q coordinate out of range"),
+            r: u16::try_from(r).expect("R coordinate out of range"),
+        }
+    }
+}
+
+/// Construct a [`HexTile`] from rows of `X` (filled) and `.` (empty) cells
+/// separated by `;`, where the `q`th cell of row `r` is at axial `(q, r)`.
+///
+/// Drawn this way the grid is sheared, so that each row sits half a cell
+/// to the right of the one above it, as in `hex_tile!(X X; X)` for the
+/// triangular trihex.
+#[macro_export]
+macro_rules! hex_tile {
+    (@row $q:tt $r:tt [$($acc:expr),*]) => {
+        $crate::hex::HexTile::new([$($acc),*])
+    };
+
+    (@row $q:tt $r:tt [$($acc:expr),*] X $($rest:tt)*) => {
+        $crate::hex_tile!(@row ($q + 1) $r [$($acc ,)* $crate::hex::HexPoint { q: $q, r: $r }] $($rest)*)
+    };
+
+    (@row $q:tt $r:tt [$($acc:expr),*] . $($rest:tt)*) => {
+        $crate::hex_tile!(@row ($q + 1) $r [$($acc),*] $($rest)*)
+    };
+
+    (@row $q:tt $r:tt [$($acc:expr),*] ; $($rest:tt)*) => {
+        $crate::hex_tile!(@row 0 ($r + 1) [$($acc),*] $($rest)*)
+    };
+
+    ($($rest:tt)*) => {
+        $crate::hex_tile!(@row 0 0 [] $($rest)*)
+    };
+}
@@ -1,5 +1,20 @@
+#![no_std]
+
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
+pub mod heuristic;
+pub mod hex;
 pub mod matrix;
+pub mod nqueens;
+pub mod packing;
 pub mod solve;
+#[cfg(feature = "sudoku")]
+pub mod sudoku;
 pub mod tile;
+pub mod tile3;
 
+pub use hex::HexTile;
 pub use tile::Tile;
+pub use tile3::Tile3;
@@ -1,9 +1,12 @@
+use alloc::collections::BTreeSet;
+use alloc::string::ToString as _;
+use alloc::vec::Vec;
 use core::cell::Cell;
 use core::fmt::Display;
 use core::iter;
 use core::ops;
-use std::collections::HashSet;
 
+#[derive(Clone)]
 pub(crate) struct Matrix {
     headers: Vec<Header>,
     nodes: Vec<Node>,
@@ -41,42 +44,36 @@ macro_rules! impl_attach {
 }
 
 impl Matrix {
-    pub(crate) fn new(column_count: u16) -> Self {
-        let header_count = 1 + column_count;
-        let mut headers = Vec::with_capacity(header_count as usize);
-
-        headers.push(Header {
-            size: Cell::new(0),
-            node: Node::new(
-                Row(0),
-                Col(0),
-                Index::DANGLING,
-                Index::DANGLING,
-                Index::header(column_count),
-                Index::header(0),
-            ),
-        });
-
-        for i in 0..column_count {
-            headers.push(Header {
+    /// Construct headers for `column_count` empty columns, reserving room
+    /// for `node_capacity` nodes so building rows doesn't reallocate.
+    pub(crate) fn with_node_capacity(column_count: u32, node_capacity: usize) -> Self {
+        // Reserve `Index::DANGLING` as a sentinel
+        let header_count = column_count
+            .checked_add(1)
+            .filter(|count| *count < Index::DANGLING.0)
+            .unwrap_or_else(|| panic!("Column count {} exceeds maximum", column_count));
+
+        let headers = (0..header_count)
+            .map(|i| Header {
                 size: Cell::new(0),
+                // Columns start as empty vertical cycles
                 node: Node::new(
                     Row(0),
-                    Col(i + 1),
-                    Index::DANGLING,
-                    Index::DANGLING,
-                    i.checked_sub(1).map(Index::header).unwrap_or(Index::GLOBAL),
+                    Col(i),
+                    Index(i),
+                    Index(i),
+                    Index(i.checked_sub(1).unwrap_or(column_count)),
                     match i + 1 {
-                        j if j == column_count => Index::GLOBAL,
-                        j => Index::header(j),
+                        j if j == header_count => Index::GLOBAL,
+                        j => Index(j),
                     },
                 ),
             })
-        }
+            .collect();
 
         Self {
             headers,
-            nodes: Vec::new(),
+            nodes: Vec::with_capacity(node_capacity),
         }
     }
 
@@ -93,22 +90,17 @@ impl Matrix {
         size.set(size.get().checked_add_signed(delta).unwrap())
     }
 
-    pub(crate) fn column(&self, col: u16) -> Col {
+    pub(crate) fn column(&self, col: u32) -> Col {
         Col(col)
     }
 
     pub(crate) fn push(&mut self, node: Node) -> Index {
-        let index = self.nodes.len();
+        let index = u32::try_from(self.headers.len() + self.nodes.len())
+            .ok()
+            .filter(|index| *index < Index::DANGLING.0)
+            .expect("Node count exceeds maximum");
         self.nodes.push(node);
-        Index((self.headers.len() + index) as u32)
-    }
-
-    pub(crate) fn map(&self) -> ColMap<Index> {
-        ColMap(
-            (0..self.headers.len())
-                .map(|i| Col(i as u16).into())
-                .collect(),
-        )
+        Index(index)
     }
 
     pub(crate) fn index_to_column(&self, index: Index) -> Col {
@@ -124,6 +116,14 @@ impl Matrix {
         r
     );
 
+    pub(crate) fn up(&self, index: Index) -> Index {
+        self[index].u.get()
+    }
+
+    pub(crate) fn down(&self, index: Index) -> Index {
+        self[index].d.get()
+    }
+
     impl_walk!(walk_up, u);
     impl_walk!(walk_down, d);
     impl_walk!(walk_left, l);
@@ -141,6 +141,68 @@ impl Matrix {
     }
 }
 
+#[cfg(any(test, feature = "invariants"))]
+impl Matrix {
+    /// Panic unless every live cycle is doubly linked and every header's
+    /// size matches the length of its column.
+    ///
+    /// Covered columns and detached nodes keep links to their former
+    /// neighbors, so only nodes reachable from the headers are checked.
+    pub(crate) fn check_invariants(&self) {
+        let check = |index: Index| {
+            let node = &self[index];
+            for next in [node.u.get(), node.d.get(), node.l.get(), node.r.get()] {
+                assert_ne!(next, Index::DANGLING, "Node {:?} links to DANGLING", index);
+            }
+            assert_eq!(
+                self[node.u.get()].d.get(),
+                index,
+                "Broken up link at {:?}",
+                index
+            );
+            assert_eq!(
+                self[node.d.get()].u.get(),
+                index,
+                "Broken down link at {:?}",
+                index
+            );
+            assert_eq!(
+                self[node.l.get()].r.get(),
+                index,
+                "Broken left link at {:?}",
+                index
+            );
+            assert_eq!(
+                self[node.r.get()].l.get(),
+                index,
+                "Broken right link at {:?}",
+                index
+            );
+        };
+
+        check(Index::GLOBAL);
+        self.walk_right(Index::GLOBAL).for_each(check);
+
+        // Columns outside the header list are still vertical cycles
+        for col in (1..self.headers.len() as u32).map(Col) {
+            let header = Index::from(col);
+            let u = self[header].u.get();
+            let d = self[header].d.get();
+            assert_eq!(self[u].d.get(), header, "Broken up link at {:?}", header);
+            assert_eq!(self[d].u.get(), header, "Broken down link at {:?}", header);
+
+            let mut size = 0;
+            for index in self.walk_down(header) {
+                check(index);
+                assert_eq!(self[index].col, col, "Node {:?} in wrong column", index);
+                size += 1;
+            }
+
+            assert_eq!(self.size(col), size, "Wrong size for column {:?}", col);
+        }
+    }
+}
+
 impl ops::Index<Index> for Matrix {
     type Output = Node;
     fn index(&self, index: Index) -> &Self::Output {
@@ -152,29 +214,37 @@ impl ops::Index<Index> for Matrix {
     }
 }
 
-impl core::fmt::Debug for Matrix {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let cols = self.headers.len() - 1;
-        let rows = self
-            .nodes
+impl Matrix {
+    fn row_count(&self) -> u32 {
+        self.nodes
             .iter()
-            .map(|node| node.row.0)
+            .map(|node| node.row.0 + 1)
             .max()
-            .unwrap_or(u32::MAX);
+            .unwrap_or(0)
+    }
 
-        let set = self
-            .walk_right(Index::GLOBAL)
+    // (row, column) pairs of nodes reachable from linked column headers
+    fn linked(&self) -> BTreeSet<(u32, u32)> {
+        self.walk_right(Index::GLOBAL)
             .flat_map(|i| {
                 self.walk_down(i)
-                    .take_while(|index| *index != Index::DANGLING)
                     .map(move |j| &self[j])
                     .map(|node| (node.row.0, node.col.0))
             })
-            .collect::<HashSet<_>>();
+            .collect()
+    }
+}
 
-        for i in 0..rows {
+/// Render the live state of the matrix, omitting covered columns and
+/// unlinked rows. Use [`Display`] to see the full static structure.
+impl core::fmt::Debug for Matrix {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let cols = self.headers.len() as u32 - 1;
+        let set = self.linked();
+
+        for i in 0..self.row_count() {
             for j in 1..=cols {
-                let char = match set.contains(&(i, j as u16)) {
+                let char = match set.contains(&(i, j)) {
                     true => "X",
                     false => ".",
                 };
@@ -188,24 +258,72 @@ impl core::fmt::Debug for Matrix {
     }
 }
 
+/// Render every node in the matrix, with rows and columns labeled.
+///
+/// Columns are labeled by the last digit of their dense index, and marked
+/// `+` if linked or `-` if covered. Nodes are drawn as `X` if reachable
+/// from a linked column, or `x` if hidden by a cover.
+impl Display for Matrix {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let cols = self.headers.len() as u32 - 1;
+        let rows = self.row_count();
+        let width = rows.saturating_sub(1).to_string().len();
+
+        let linked = self.linked();
+        let active = self
+            .walk_right(Index::GLOBAL)
+            .map(|index| self[index].col.0)
+            .collect::<BTreeSet<_>>();
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| (node.row.0, node.col.0))
+            .collect::<BTreeSet<_>>();
+
+        write!(f, "{:width$} ", "")?;
+        for j in 1..=cols {
+            write!(f, "{}", (j - 1) % 10)?;
+        }
+        writeln!(f)?;
+
+        write!(f, "{:width$} ", "")?;
+        for j in 1..=cols {
+            let char = match active.contains(&j) {
+                true => "+",
+                false => "-",
+            };
+            write!(f, "{}", char)?;
+        }
+        writeln!(f)?;
+
+        for i in 0..rows {
+            write!(f, "{:>width$} ", i)?;
+            for j in 1..=cols {
+                let char = match (linked.contains(&(i, j)), nodes.contains(&(i, j))) {
+                    (true, _) => "X",
+                    (false, true) => "x",
+                    (false, false) => ".",
+                };
+
+                write!(f, "{}", char)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Index(u32);
 
 impl Index {
     pub(crate) const GLOBAL: Self = Self(0);
     pub(crate) const DANGLING: Self = Self(u32::MAX);
-
-    fn header(col: u16) -> Self {
-        Self(1 + col as u32)
-    }
-
-    pub(crate) fn prev(&self) -> Self {
-        Self(self.0 - 1)
-    }
 }
 
 impl Display for Index {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         self.0.fmt(f)
     }
 }
@@ -226,57 +344,33 @@ impl From<Row> for usize {
 }
 
 impl Display for Row {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         self.0.fmt(f)
     }
 }
 
-pub(crate) struct ColMap<T>(Vec<T>);
-
-impl<T> ColMap<T> {
-    pub(crate) fn iter(&self) -> impl Iterator<Item = (Col, &T)> + '_ {
-        self.0
-            .iter()
-            .enumerate()
-            .map(|(col, item)| (Col(col as u16), item))
-    }
-}
-
-impl<T> ops::Index<Col> for ColMap<T> {
-    type Output = T;
-    fn index(&self, index: Col) -> &Self::Output {
-        &self.0[index.0 as usize]
-    }
-}
-
-impl<T> ops::IndexMut<Col> for ColMap<T> {
-    fn index_mut(&mut self, index: Col) -> &mut Self::Output {
-        &mut self.0[index.0 as usize]
-    }
-}
-
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub(crate) struct Col(u16);
+pub struct Col(u32);
 
 impl From<Col> for Index {
     fn from(col: Col) -> Self {
-        Self(col.0 as u32)
+        Self(col.0)
     }
 }
 
-impl From<Col> for u16 {
+impl From<Col> for u32 {
     fn from(col: Col) -> Self {
         col.0
     }
 }
 
 impl Display for Col {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         self.0.fmt(f)
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 struct Header {
     size: Cell<u32>,
     node: Node,
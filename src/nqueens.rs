@@ -0,0 +1,73 @@
+//! Exact cover encoding of the N-Queens problem.
+//!
+//! Each row of the matrix places a queen on one square, covering its rank
+//! and file (primary columns) and its two diagonals (secondary columns,
+//! since not every diagonal holds a queen).
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::solve;
+use crate::solve::Search;
+use crate::solve::Solver;
+use crate::solve::StepResult;
+
+/// Return the number of ways to place `n` non-attacking queens on an
+/// `n` by `n` board.
+pub fn count(n: usize) -> usize {
+    solver(n).solve_count()
+}
+
+/// Yield each solution as the file of the queen on each rank.
+///
+/// Solutions are found lazily, with the search advanced only as far as
+/// the next one, so stopping early skips the rest of the search.
+pub fn solutions(n: usize) -> impl Iterator<Item = Vec<usize>> {
+    let mut search = Search::new(solver(n));
+
+    core::iter::from_fn(move || loop {
+        match search.step() {
+            StepResult::Solution(rows) => {
+                let mut files = vec![0; n];
+                for row in rows {
+                    files[row / n] = row % n;
+                }
+                return Some(files);
+            }
+            StepResult::Done => return None,
+            StepResult::Select(_) | StepResult::Backtrack => (),
+        }
+    })
+}
+
+fn solver(n: usize) -> Solver {
+    let n = u32::try_from(n)
+        .ok()
+        .filter(|n| n.checked_mul(6).is_some())
+        .expect("Board is too large to encode in 32-bit columns");
+
+    let squares = (0..n)
+        .flat_map(|rank| (0..n).map(move |file| Square { n, rank, file }))
+        .collect::<Vec<_>>();
+
+    Solver::with_secondary(&squares, |col| col >= 2 * n)
+}
+
+struct Square {
+    n: u32,
+    rank: u32,
+    file: u32,
+}
+
+impl solve::Row for Square {
+    fn iter(&self) -> impl Iterator<Item = u32> {
+        let n = self.n;
+        [
+            self.rank,
+            n + self.file,
+            2 * n + self.rank + self.file,
+            4 * n + self.rank + n - 1 - self.file,
+        ]
+        .into_iter()
+    }
+}
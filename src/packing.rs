@@ -0,0 +1,117 @@
+//! Placement of polyominoes on rectangular boards.
+
+use alloc::vec::Vec;
+
+use crate::tile::Point;
+use crate::tile::TileOrbit;
+use crate::Tile;
+
+/// Return every placement of every distinct orientation of `tiles` within
+/// a `rows` by `cols` board, keeping only those whose cells all satisfy
+/// `filter`.
+///
+/// Each placement is tagged with the index of its source tile in `tiles`.
+/// Placements are grouped by tile, then ordered by orientation, then by
+/// translation in row-major order.
+pub fn place_all<const N: usize, F: FnMut(Point) -> bool>(
+    tiles: &[Tile<N>],
+    rows: u16,
+    cols: u16,
+    mut filter: F,
+) -> Vec<(usize, Tile<N>)> {
+    let mut placements = Vec::new();
+
+    for (id, tile) in tiles.iter().enumerate() {
+        for orientation in TileOrbit::new(tile).iter() {
+            for di in 0..rows {
+                for dj in 0..cols {
+                    let Some(placed) = orientation.translate(di, dj) else {
+                        continue;
+                    };
+
+                    if placed
+                        .cells()
+                        .all(|point| point.i < rows && point.j < cols && filter(point))
+                    {
+                        placements.push((id, placed));
+                    }
+                }
+            }
+        }
+    }
+
+    placements
+}
+
+/// Drop placements of the tile with index `piece` that aren't the least
+/// of their images under the symmetries of a `rows` by `cols` board,
+/// keeping every other placement.
+///
+/// Every solution is equivalent under board symmetry to exactly one
+/// that places `piece` in the remaining fundamental domain, provided no
+/// remaining placement of `piece` is fixed by a nontrivial symmetry. A
+/// rectangle has four symmetries and a square eight, so the search
+/// shrinks by about that factor, and solutions need no deduplication.
+///
+/// Choose `piece` so that its placements are never symmetric, e.g. the
+/// X pentomino on a non-square board with even side lengths. On a square
+/// board the X can sit centered on a diagonal, fixed by the transpose;
+/// pieces without any symmetry of their own, like the R, are always safe.
+///
+/// # Panics
+///
+/// Panics if some placement of `piece` is fixed by a nontrivial symmetry
+/// of the board, since solutions with `piece` there would be counted more
+/// than once.
+pub fn constrain_to_fundamental_domain<const N: usize>(
+    placements: &[(usize, Tile<N>)],
+    piece: usize,
+    rows: u16,
+    cols: u16,
+) -> Vec<(usize, Tile<N>)> {
+    // Each symmetry optionally transposes, then flips rows and columns
+    let symmetries = [false, true]
+        .into_iter()
+        .filter(|transpose| !transpose || rows == cols)
+        .flat_map(|transpose| {
+            [(false, false), (false, true), (true, false), (true, true)]
+                .map(|(flip_i, flip_j)| (transpose, flip_i, flip_j))
+        })
+        .collect::<Vec<_>>();
+
+    let image = |tile: &Tile<N>, (transpose, flip_i, flip_j): (bool, bool, bool)| {
+        Tile::new(tile.as_ref().map(|point| {
+            let Point { i, j } = match transpose {
+                true => Point {
+                    i: point.j,
+                    j: point.i,
+                },
+                false => point,
+            };
+            Point {
+                i: if flip_i { rows - 1 - i } else { i },
+                j: if flip_j { cols - 1 - j } else { j },
+            }
+        }))
+    };
+
+    placements
+        .iter()
+        .filter(|(id, tile)| {
+            if *id != piece {
+                return true;
+            }
+
+            // The first symmetry is the identity
+            let mut images = symmetries.iter().map(|symmetry| image(tile, *symmetry));
+            assert!(
+                images.clone().skip(1).all(|image| image != *tile),
+                "Placement {:?} of piece {} is fixed by a board symmetry",
+                tile,
+                piece,
+            );
+            images.all(|image| *tile <= image)
+        })
+        .copied()
+        .collect()
+}
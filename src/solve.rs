@@ -1,20 +1,232 @@
+use alloc::collections::BTreeMap;
+use alloc::collections::BTreeSet;
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::Cell;
+use core::cmp::Ordering;
+use core::fmt::Display;
+use core::iter;
 use core::ops::ControlFlow;
-use std::collections::BTreeSet;
-use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::io;
 
+use crate::heuristic::Heuristic;
+use crate::heuristic::MinRemaining;
+use crate::heuristic::MinRemainingBy;
 use crate::matrix;
 use crate::matrix::Matrix;
+use crate::tile;
+use crate::Tile;
 
+mod search;
+
+pub use search::Phase;
+pub use search::Search;
+pub use search::SearchState;
+pub use search::StepResult;
+
+/// Exact cover solver over a sparse matrix of dancing links.
+///
+/// # Thread safety
+///
+/// Searches mutate the links in place through [`Cell`]s, so `Solver` is
+/// [`Send`] but not [`Sync`]: it can be moved into another thread, as in
+/// `std::thread::spawn(move || solver.solve_count())`, but not shared
+/// between threads. To search in parallel, [`Clone`] one solver per thread.
+#[derive(Clone)]
 pub struct Solver {
     matrix: Matrix,
+
+    // Sorted sparse column IDs, indexed by dense column - 1
+    columns: Vec<u32>,
+
+    // First node of each row, or `Index::DANGLING` for empty rows
+    rows: Vec<matrix::Index>,
+
+    // Present only if some column has bounds other than `(0, 1)` or `(1, 1)`
+    multiplicity: Option<Multiplicity>,
+
+    // Comparator for breaking ties between columns of equal size
+    tiebreak: Option<Tiebreak>,
+
+    // Rows whose nodes are unlinked from their columns
+    disabled: BTreeSet<usize>,
+}
+
+// Keep `Solver` movable across threads, per the contract above
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    assert_send::<Solver>();
+};
+
+type Tiebreak = Arc<dyn Fn(matrix::Col, matrix::Col) -> Ordering + Send + Sync>;
+
+#[derive(Clone)]
+struct Multiplicity {
+    // Lower and upper bounds, indexed by dense column
+    bounds: Vec<(u16, u16)>,
+
+    // Number of selected rows covering each dense column
+    counts: Vec<Cell<u16>>,
+}
+
+impl Multiplicity {
+    fn bounds(&self, col: matrix::Col) -> (u16, u16) {
+        self.bounds[u32::from(col) as usize]
+    }
+
+    fn count(&self, col: matrix::Col) -> &Cell<u16> {
+        &self.counts[u32::from(col) as usize]
+    }
+
+    // Number of additional rows required to meet the lower bound
+    fn deficit(&self, col: matrix::Col) -> u32 {
+        let (lower, _) = self.bounds(col);
+        lower.saturating_sub(self.count(col).get()) as u32
+    }
 }
 
 pub trait Row {
-    fn iter(&self) -> impl Iterator<Item = u16>;
+    fn iter(&self) -> impl Iterator<Item = u32>;
+}
+
+/// A [`Row`] carrying an identifier, so solutions can be reported as
+/// identifiers rather than row indices.
+///
+/// This is separate from [`Row`] so existing implementations needn't name
+/// an identifier type.
+pub trait Identified: Row {
+    type Id: Clone;
+
+    fn id(&self) -> Self::Id;
+}
+
+/// Encode the cell in row `i` and column `j` of a grid `width` columns wide
+/// as the column ID `i * width + j`, for use in [`Row`] implementations.
+///
+/// Returns `None` if `j` is outside the grid or the ID would overflow `u32`,
+/// rather than silently colliding with another cell.
+pub fn pack_coords(i: u32, j: u32, width: u32) -> Option<u32> {
+    if j >= width {
+        return None;
+    }
+
+    i.checked_mul(width)?.checked_add(j)
 }
 
 impl Solver {
+    /// Construct a solver where every column must be covered exactly once.
+    ///
+    /// Ties between columns with equally few remaining rows are broken in
+    /// favor of the least sparse ID, so renumbering columns can change the
+    /// shape and size of the search. See [`Solver::new_ordered`] and
+    /// [`Solver::set_tiebreak`] to control this explicitly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some row contains the same column more than once.
     pub fn new<R: Row>(rows: &[R]) -> Self {
+        Self::with_secondary(rows, |_| false)
+    }
+
+    /// Construct a solver like [`Solver::new`], but add a slack row covering
+    /// only that column for each sparse column satisfying `slack_columns`.
+    ///
+    /// Selecting a slack row stands for leaving its column uncovered, so
+    /// flagged columns may go uncovered without a separate partial-cover
+    /// search. Slack rows are numbered after `rows`, in increasing order of
+    /// sparse ID, so indices of at least `rows.len()` in a solution mark
+    /// uncovered columns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some row contains the same column more than once.
+    pub fn with_slack<R: Row, F: Fn(u32) -> bool>(rows: &[R], slack_columns: F) -> Self {
+        let mut rows = rows
+            .iter()
+            .map(|row| Columns(row.iter().collect()))
+            .collect::<Vec<_>>();
+
+        let slack = rows
+            .iter()
+            .flat_map(Row::iter)
+            .filter(|sparse| slack_columns(*sparse))
+            .collect::<BTreeSet<_>>();
+
+        rows.extend(slack.into_iter().map(|sparse| Columns(vec![sparse])));
+        Self::new(&rows)
+    }
+
+    /// Construct a solver like [`Solver::new`], but breaking ties between
+    /// columns in order of their first appearance in `order` instead.
+    ///
+    /// Columns missing from `order` come last, by sparse ID. Passing
+    /// `rows.iter().flat_map(Row::iter)` orders columns as first seen in
+    /// the rows.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some row contains the same column more than once, or if
+    /// `order` contains a column no row covers.
+    pub fn new_ordered<R: Row, I: IntoIterator<Item = u32>>(rows: &[R], order: I) -> Self {
+        let solver = Self::new(rows);
+        let mut seen = vec![false; solver.columns.len()];
+        let mut dense = Vec::with_capacity(solver.columns.len());
+
+        for sparse in order {
+            let index = solver
+                .dense_column(sparse)
+                .unwrap_or_else(|| panic!("Column {} does not exist", sparse));
+            if !core::mem::replace(&mut seen[index as usize], true) {
+                dense.push(index);
+            }
+        }
+
+        dense.extend((0..solver.columns.len() as u32).filter(|index| !seen[*index as usize]));
+
+        // Relink the header list in the requested order
+        let mut left = matrix::Index::GLOBAL;
+        for index in dense {
+            let col = solver.matrix.column(index + 1).into();
+            solver.matrix.attach_horizontal(left, col);
+            left = col;
+        }
+        solver.matrix.attach_horizontal(left, matrix::Index::GLOBAL);
+
+        solver
+    }
+
+    /// Construct a solver where sparse columns satisfying `is_secondary` may be
+    /// covered at most once, instead of exactly once.
+    ///
+    /// Secondary columns are never chosen for branching, so solutions are
+    /// complete once every primary column is covered.
+    pub fn with_secondary<R: Row, F: Fn(u32) -> bool>(rows: &[R], is_secondary: F) -> Self {
+        Self::with_multiplicity(rows, |col| match is_secondary(col) {
+            true => (0, 1),
+            false => (1, 1),
+        })
+    }
+
+    /// Construct a solver where each sparse column must be covered between
+    /// `lower` and `upper` times inclusive, as given by `bounds`.
+    ///
+    /// Bounds of `(1, 1)` and `(0, 1)` correspond to primary and secondary
+    /// columns respectively. Columns with a lower bound of zero are never
+    /// chosen for branching.
+    ///
+    /// [`Search`], [`Solver::solve_with_fixed`], [`Solver::would_orphan`],
+    /// [`Solver::solve_trace`], [`Solver::solve_partial`],
+    /// [`Solver::solve_logged`], and [`Solver::estimate_solutions`] only
+    /// cover columns once, so they panic if any upper bound exceeds one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some row contains the same column more than once, if some
+    /// column has an upper bound of zero, or if some column has a lower bound
+    /// exceeding its upper bound.
+    pub fn with_multiplicity<R: Row, F: Fn(u32) -> (u16, u16)>(rows: &[R], bounds: F) -> Self {
         let dense_to_sparse = rows
             .iter()
             .flat_map(Row::iter)
@@ -22,196 +234,3361 @@ impl Solver {
             .into_iter()
             .collect::<Vec<_>>();
 
-        let sparse_to_dense = dense_to_sparse
-            .iter()
-            .copied()
-            .enumerate()
-            .map(|(dense, sparse)| (sparse, dense as u16 + 1))
-            .collect::<HashMap<_, _>>();
+        let mut solver = Self {
+            matrix: Matrix::with_node_capacity(
+                dense_to_sparse.len() as u32,
+                rows.iter().flat_map(Row::iter).count(),
+            ),
+            columns: dense_to_sparse,
+            rows: Vec::with_capacity(rows.len()),
+            multiplicity: None,
+            tiebreak: None,
+            disabled: BTreeSet::new(),
+        };
+
+        for row in rows {
+            solver.add_row(row.iter());
+        }
+
+        let dense_to_sparse = &solver.columns;
+        let matrix = &solver.matrix;
+
+        // Global header has no bounds
+        let bounds = iter::once((0, 0))
+            .chain(dense_to_sparse.iter().map(|sparse| {
+                let (lower, upper) = bounds(*sparse);
+                assert!(
+                    0 < upper && lower <= upper,
+                    "Invalid bounds ({}, {}) for column {}",
+                    lower,
+                    upper,
+                    sparse,
+                );
+                (lower, upper)
+            }))
+            .collect::<Vec<_>>();
 
-        let mut matrix = Matrix::new(dense_to_sparse.len() as u16);
-        let mut prev = matrix.map();
+        // Unlink secondary columns from the header list
+        for (dense, (lower, _)) in bounds.iter().enumerate().skip(1) {
+            if *lower == 0 {
+                let col = matrix.column(dense as u32).into();
+                matrix.detach_horizontal(col);
+                matrix.attach_horizontal(col, col);
+            }
+        }
 
-        for (row, r) in rows
+        solver.multiplicity = bounds
             .iter()
-            .enumerate()
-            .map(|(i, row)| (matrix::Row::new(i as u32), row))
-        {
-            let mut head = None;
-            let mut tail = None;
+            .skip(1)
+            .any(|(lower, upper)| *lower > 1 || *upper > 1)
+            .then(|| Multiplicity {
+                counts: vec![Cell::new(0); bounds.len()],
+                bounds,
+            });
 
-            for sparse in r.iter() {
-                let dense = sparse_to_dense[&sparse];
-                let col = matrix.column(dense);
+        solver
+    }
 
-                matrix.update_size(col, 1);
+    /// Link a new row covering `columns` into the matrix, returning its row index.
+    ///
+    /// Each column must already be covered by some row passed at construction,
+    /// and keeps the bounds it was given then. The matrix must be fully
+    /// uncovered, which the borrow checker guarantees outside of a search.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some column does not exist, or appears more than once.
+    pub fn add_row<I: IntoIterator<Item = u32>>(&mut self, columns: I) -> usize {
+        let row = matrix::Row::new(self.rows.len() as u32);
+        let mut seen = BTreeSet::new();
+        let mut head = None;
+        let mut tail = None;
 
-                let index = matrix.push(matrix::Node::dangling(row, col));
-                let up = prev[col];
+        for sparse in columns {
+            assert!(
+                seen.insert(sparse),
+                "Row {} contains column {} more than once",
+                row,
+                sparse,
+            );
 
-                matrix.attach_vertical(up, index);
+            let dense = self
+                .columns
+                .binary_search(&sparse)
+                .unwrap_or_else(|_| panic!("Column {} does not exist", sparse));
+            let col = self.matrix.column(dense as u32 + 1);
 
-                if head.is_some() {
-                    let left = index.prev();
-                    matrix.attach_horizontal(left, index);
-                }
+            self.matrix.update_size(col, 1);
 
-                prev[col] = index;
+            // Splice into the bottom of the column's cycle
+            let index = self.matrix.push(matrix::Node::dangling(row, col));
+            let up = self.matrix.up(col.into());
 
-                head.get_or_insert(index);
-                tail = Some(index);
-            }
+            self.matrix.attach_vertical(up, index);
+            self.matrix.attach_vertical(index, col.into());
 
-            if let (Some(head), Some(tail)) = (head, tail) {
-                matrix.attach_horizontal(tail, head);
+            if let Some(left) = tail {
+                self.matrix.attach_horizontal(left, index);
             }
+
+            head.get_or_insert(index);
+            tail = Some(index);
+        }
+
+        if let (Some(head), Some(tail)) = (head, tail) {
+            self.matrix.attach_horizontal(tail, head);
+        }
+
+        self.rows.push(head.unwrap_or(matrix::Index::DANGLING));
+        self.rows.len() - 1
+    }
+
+    /// Unlink `row` from its columns, so searches skip it until it is
+    /// re-enabled. Returns `false` if the row was already disabled.
+    ///
+    /// This is much cheaper than rebuilding the solver without the row, and
+    /// combined with [`Clone`] lets variants of an instance be explored
+    /// without reconstruction. Column sizes are kept up to date.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row` does not exist.
+    pub fn disable_row(&mut self, row: usize) -> bool {
+        let head = self.rows[row];
+        if !self.disabled.insert(row) {
+            return false;
+        }
+
+        if head != matrix::Index::DANGLING {
+            self.detach_row(head);
+        }
+
+        true
+    }
+
+    /// Link a row disabled by [`Solver::disable_row`] back into its columns,
+    /// in its original position. Returns `false` if the row was not disabled.
+    ///
+    /// Rows may be enabled in any order, unlike the last-in first-out order
+    /// dancing links otherwise requires.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row` does not exist.
+    pub fn enable_row(&mut self, row: usize) -> bool {
+        let head = self.rows[row];
+        if !self.disabled.remove(&row) {
+            return false;
+        }
+
+        if head == matrix::Index::DANGLING {
+            return true;
+        }
+
+        let row = matrix::Row::new(row as u32);
+        for index in iter::once(head).chain(self.matrix.walk_right(head)) {
+            let col = self.matrix.index_to_column(index);
+
+            // Neighbors may have been disabled since, so find the first
+            // remaining row below this one rather than trusting stale links
+            let below = self
+                .matrix
+                .walk_down(col.into())
+                .find(|below| self.matrix[*below].row > row)
+                .unwrap_or(col.into());
+            let above = self.matrix.up(below);
+
+            self.matrix.attach_vertical(above, index);
+            self.matrix.attach_vertical(index, below);
+            self.matrix.update_size(col, 1);
         }
 
-        // Complete column cycles
-        for (col, index) in prev.iter() {
-            matrix.attach_vertical(*index, col.into());
+        true
+    }
+
+    /// Disable every row for which `keep` returns `false`, as if by
+    /// [`Solver::disable_row`], leaving the rest untouched.
+    ///
+    /// Dropped rows can be restored with [`Solver::enable_row`]. A primary
+    /// column left without rows is reported by
+    /// [`Solver::uncoverable_columns`], and the problem has no solutions.
+    pub fn retain<F: Fn(usize) -> bool>(&mut self, keep: F) {
+        for row in 0..self.rows.len() {
+            if !keep(row) {
+                self.disable_row(row);
+            }
         }
+    }
 
-        Self { matrix }
+    /// Return whether `row` is currently disabled.
+    pub fn is_row_disabled(&self, row: usize) -> bool {
+        self.disabled.contains(&row)
     }
 
+    /// Return the number of nodes in the matrix, i.e. the total number of
+    /// columns across all rows.
     #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         self.matrix.len()
     }
 
-    pub fn solve_count(&self) -> usize {
-        let mut solution = Vec::new();
-        let mut count = 0;
-        self.solve_inner(&mut solution, &mut |_| {
-            count += 1;
-            ControlFlow::<(), ()>::Continue(())
-        });
-        count
+    /// Panic if the linked structure of the matrix is inconsistent, for use
+    /// in tests and fuzz targets. Only available with the `invariants`
+    /// feature.
+    #[cfg(any(test, feature = "invariants"))]
+    pub fn check_invariants(&self) {
+        self.matrix.check_invariants();
     }
 
-    pub fn solve<T, F: FnMut(&mut [usize]) -> ControlFlow<T, ()>>(
-        &self,
-        mut inspect: F,
-    ) -> Option<T> {
-        let mut solution = Vec::new();
-        let mut buffer = Vec::new();
-        self.solve_inner(&mut solution, &mut |solution| {
-            buffer.clear();
-            buffer.extend(
-                solution
-                    .iter()
-                    .map(|index| usize::from(self.matrix[*index].row)),
-            );
-            inspect(&mut buffer)
-        })
+    /// Return the number of rows, including rows without columns.
+    pub fn row_count(&self) -> usize {
+        self.rows.len()
     }
 
-    fn solve_inner<T, F: FnMut(&[matrix::Index]) -> ControlFlow<T, ()>>(
-        &self,
-        solution: &mut Vec<matrix::Index>,
-        inspect: &mut F,
-    ) -> Option<T> {
-        let Some(col) = self
-            .matrix
+    /// Return the number of distinct sparse columns across all rows.
+    pub fn column_count(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Return the sparse ID of the `dense`th column, where dense columns are
+    /// numbered from zero in increasing order of sparse ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dense` is not less than [`Solver::column_count`].
+    pub fn sparse_column(&self, dense: u32) -> u32 {
+        self.columns[dense as usize]
+    }
+
+    /// Return the dense index of the column with sparse ID `sparse`, or
+    /// `None` if no row covers it.
+    pub fn dense_column(&self, sparse: u32) -> Option<u32> {
+        self.columns
+            .binary_search(&sparse)
+            .ok()
+            .map(|dense| dense as u32)
+    }
+
+    /// Return `false` if some primary column has no rows left to cover it,
+    /// in which case the problem is trivially unsatisfiable.
+    ///
+    /// A `true` result does not guarantee a solution exists.
+    pub fn is_satisfiable_preliminary(&self) -> bool {
+        self.uncoverable_columns().is_empty()
+    }
+
+    /// Return the sparse IDs of primary columns with too few rows left to
+    /// meet their lower bounds.
+    pub fn uncoverable_columns(&self) -> Vec<u32> {
+        self.matrix
             .walk_right(matrix::Index::GLOBAL)
             .map(|index| self.matrix.index_to_column(index))
-            .min_by_key(|col| self.matrix.size(*col))
-        else {
-            match inspect(solution) {
-                ControlFlow::Continue(()) => return None,
-                ControlFlow::Break(out) => return Some(out),
+            .filter(|col| match &self.multiplicity {
+                None => self.matrix.size(*col) == 0,
+                Some(multiplicity) => self.matrix.size(*col) < multiplicity.deficit(*col),
+            })
+            .map(|col| self.sparse(col))
+            .collect()
+    }
+
+    /// Return the sparse ID and current row count of each active primary column.
+    ///
+    /// This may be called from within a `solve` callback, in which case the
+    /// matrix reflects the covered state at that solution. Every primary column
+    /// is covered at a solution, so the result is empty there; step a
+    /// [`Search`] to observe partial states instead.
+    pub fn column_sizes(&self) -> Vec<(u32, u32)> {
+        self.matrix
+            .walk_right(matrix::Index::GLOBAL)
+            .map(|index| self.matrix.index_to_column(index))
+            .map(|col| (self.sparse(col), self.matrix.size(col)))
+            .collect()
+    }
+
+    /// Return the dense index of every column alongside the number of rows
+    /// referencing it, including disabled rows and secondary columns.
+    ///
+    /// Columns are created only for sparse IDs some row covers, so gaps in
+    /// the sparse ID space never become columns and every count is nonzero.
+    /// Comparing counts against the intended encoding catches constraints
+    /// that were meant to be distinct but share a sparse ID.
+    pub fn column_report(&self) -> Vec<(u32, u32)> {
+        let mut counts = vec![0; self.columns.len()];
+        for head in self
+            .rows
+            .iter()
+            .filter(|head| **head != matrix::Index::DANGLING)
+        {
+            for col in self.row_columns(*head) {
+                counts[u32::from(col) as usize - 1] += 1;
             }
-        };
+        }
 
-        self.cover(col);
+        debug_assert!(
+            counts.iter().all(|count| *count > 0),
+            "Some column is referenced by no row",
+        );
 
-        for i in self.matrix.walk_down(col.into()) {
-            solution.push(i);
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(dense, count)| (dense as u32, count))
+            .collect()
+    }
 
-            for j in self
-                .matrix
-                .walk_right(i)
-                .map(|j| self.matrix.index_to_column(j))
-            {
-                self.cover(j);
-            }
+    /// Write the instance as a header line with the row and column counts,
+    /// followed by one line per row listing its sparse column IDs.
+    ///
+    /// Empty rows are written as empty lines. Whether a column is secondary
+    /// is not recorded.
+    #[cfg(feature = "std")]
+    pub fn write_instance(&self, w: &mut impl io::Write) -> io::Result<()> {
+        writeln!(w, "{} {}", self.row_count(), self.column_count())?;
 
-            if let Some(out) = self.solve_inner(solution, inspect) {
-                return Some(out);
+        for head in &self.rows {
+            if *head != matrix::Index::DANGLING {
+                let mut columns = self.row_columns(*head).map(|col| self.sparse(col));
+                if let Some(col) = columns.next() {
+                    write!(w, "{}", col)?;
+                }
+                for col in columns {
+                    write!(w, " {}", col)?;
+                }
             }
+            writeln!(w)?;
+        }
 
-            for j in self
-                .matrix
-                .walk_left(i)
-                .map(|j| self.matrix.index_to_column(j))
-            {
-                self.uncover(j);
+        Ok(())
+    }
+
+    /// Parse an instance with one row per non-empty line, each listing its
+    /// sparse column IDs separated by whitespace.
+    ///
+    /// An optional first line `primary N` makes columns below `N` primary
+    /// and the rest secondary; otherwise every column is primary. Unlike
+    /// [`Solver::write_instance`], this format has no header counts and
+    /// can't express empty rows, so it is easier to write by hand.
+    pub fn from_text(input: &str) -> Result<Self, ParseInstanceError> {
+        let mut lines = input
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .peekable();
+
+        let mut primary = None;
+        if let Some((line, text)) = lines.peek() {
+            if let Some(count) = text.strip_prefix("primary") {
+                let count = count
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|_| ParseInstanceError::InvalidPrimary { line: *line })?;
+                primary = Some(count);
+                lines.next();
             }
+        }
 
-            solution.pop();
+        let mut rows = Vec::new();
+        for (line, text) in lines {
+            let mut columns = Vec::new();
+            for token in text.split_whitespace() {
+                let column = token
+                    .parse::<u32>()
+                    .map_err(|_| ParseInstanceError::InvalidColumn { line })?;
+                if columns.contains(&column) {
+                    return Err(ParseInstanceError::DuplicateColumn { line, column });
+                }
+                columns.push(column);
+            }
+            rows.push(Columns(columns));
         }
 
-        self.uncover(col);
-        None
+        Ok(Self::with_secondary(&rows, |column| {
+            primary.is_some_and(|primary| column >= primary)
+        }))
     }
 
-    fn cover(&self, col: matrix::Col) {
-        let col = col.into();
+    /// Read an instance in the format produced by [`Solver::write_instance`].
+    #[cfg(feature = "std")]
+    pub fn read_instance(r: impl io::BufRead) -> io::Result<Self> {
+        fn invalid<E: Into<std::boxed::Box<dyn core::error::Error + Send + Sync>>>(
+            error: E,
+        ) -> io::Error {
+            io::Error::new(io::ErrorKind::InvalidData, error)
+        }
 
-        self.matrix.detach_horizontal(col);
+        let mut lines = r.lines();
 
-        for i in self.matrix.walk_down(col) {
-            for j in self.matrix.walk_right(i) {
-                self.matrix.detach_vertical(j);
+        let header = lines.next().ok_or_else(|| invalid("missing header"))??;
+        let counts = header
+            .split_whitespace()
+            .map(str::parse::<usize>)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(invalid)?;
 
-                let col = self.matrix.index_to_column(j);
-                self.matrix.update_size(col, -1);
+        let [row_count, column_count] = counts[..] else {
+            return Err(invalid("expected row and column counts"));
+        };
+
+        let mut builder = SolverBuilder::new();
+
+        for line in lines.take(row_count) {
+            let columns = line?
+                .split_whitespace()
+                .map(str::parse::<u32>)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(invalid)?;
+
+            if columns.iter().collect::<BTreeSet<_>>().len() != columns.len() {
+                return Err(invalid("duplicate column within row"));
             }
+
+            builder.add_row(columns);
         }
-    }
 
-    fn uncover(&self, col: matrix::Col) {
-        let col = col.into();
+        let solver = builder.build();
 
-        for i in self.matrix.walk_up(col) {
-            for j in self.matrix.walk_left(i) {
-                self.matrix.reattach_vertical(j);
+        if solver.row_count() != row_count {
+            return Err(invalid(std::format!(
+                "expected {} rows, found {}",
+                row_count,
+                solver.row_count(),
+            )));
+        }
 
-                let col = self.matrix.index_to_column(j);
-                self.matrix.update_size(col, 1);
-            }
+        if solver.column_count() != column_count {
+            return Err(invalid(std::format!(
+                "expected {} columns, found {}",
+                column_count,
+                solver.column_count(),
+            )));
         }
 
-        self.matrix.reattach_horizontal(col);
+        Ok(solver)
     }
-}
 
-#[test]
-fn smoke() {
-    struct Row(u8);
+    /// Break ties between columns with equally few remaining rows using
+    /// `tiebreak`, preferring the lesser column, instead of the leftmost.
+    ///
+    /// Applies to every method that doesn't take an explicit [`Heuristic`].
+    /// Columns can be mapped to their dense index `u32::from(col) - 1` and
+    /// then to their sparse ID with [`Solver::sparse_column`].
+    pub fn set_tiebreak<F: Fn(matrix::Col, matrix::Col) -> Ordering + Send + Sync + 'static>(
+        &mut self,
+        tiebreak: F,
+    ) {
+        self.tiebreak = Some(Arc::new(tiebreak));
+    }
 
-    impl crate::solve::Row for Row {
-        fn iter(&self) -> impl Iterator<Item = u16> {
-            (0..8).filter(|bit| (self.0 >> bit) & 1 > 0)
+    /// Break ties between columns with equally few remaining rows by
+    /// `priority[dense]`, preferring lesser values, then the leftmost.
+    ///
+    /// So the search branches on the column minimizing `(size, priority)`,
+    /// which lets a caller who knows the instance, say, steer toward cells
+    /// at the center of a board. Replaces any [`Solver::set_tiebreak`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `priority` doesn't have one entry per column.
+    pub fn set_column_priority(&mut self, priority: Vec<u32>) {
+        assert_eq!(
+            priority.len(),
+            self.column_count(),
+            "Expected one priority per column",
+        );
+        self.set_tiebreak(move |a, b| {
+            priority[u32::from(a) as usize - 1].cmp(&priority[u32::from(b) as usize - 1])
+        });
+    }
+
+    // Branch on the column with fewest remaining rows, breaking ties
+    // by `self.tiebreak` if set.
+    fn heuristic(&self) -> DefaultHeuristic<'_> {
+        DefaultHeuristic(self.tiebreak.as_deref())
+    }
+
+    // Like `heuristic`, but choose empty columns instead of giving up.
+    fn min_column(&self) -> Option<matrix::Col> {
+        self.matrix
+            .walk_right(matrix::Index::GLOBAL)
+            .map(|index| self.matrix.index_to_column(index))
+            .min_by(|a, b| {
+                let tiebreak = || match self.tiebreak.as_deref() {
+                    None => Ordering::Equal,
+                    Some(tiebreak) => tiebreak(*a, *b),
+                };
+                self.matrix
+                    .size(*a)
+                    .cmp(&self.matrix.size(*b))
+                    .then_with(tiebreak)
+            })
+    }
+
+    fn sparse(&self, col: matrix::Col) -> u32 {
+        self.columns[u32::from(col) as usize - 1]
+    }
+
+    pub fn solve_count(&self) -> usize {
+        let mut solution = Vec::new();
+        let mut count = 0;
+        self.solve_inner(
+            &self.heuristic(),
+            &mut |_| false,
+            &mut solution,
+            &mut |_| {
+                count += 1;
+                ControlFlow::<(), ()>::Continue(())
+            },
+        );
+        count
+    }
+
+    /// Count solutions for as long as `pred` holds for the count so far,
+    /// stopping as soon as it returns `false`.
+    ///
+    /// For example, `count_while(|count| count < max)` counts at most `max`
+    /// solutions, which is enough to tell whether there are at least `max`.
+    pub fn count_while<P: FnMut(usize) -> bool>(&self, mut pred: P) -> usize {
+        let mut count = 0;
+
+        if !pred(count) {
+            return count;
         }
+
+        self.solve_inner(
+            &self.heuristic(),
+            &mut |_| false,
+            &mut Vec::new(),
+            &mut |_| {
+                count += 1;
+                match pred(count) {
+                    true => ControlFlow::Continue(()),
+                    false => ControlFlow::Break(()),
+                }
+            },
+        );
+
+        count
     }
 
-    let solver = Solver::new(&[
-        Row(0b0110100),
-        Row(0b1001001),
-        Row(0b0100110),
-        Row(0b0001001),
-        Row(0b1000010),
-        Row(0b1011000),
-    ]);
+    pub fn solve<T, F: FnMut(&mut [usize]) -> ControlFlow<T, ()>>(&self, inspect: F) -> Option<T> {
+        self.solve_rows(&self.heuristic(), |_| false, &mut Vec::new(), inspect)
+    }
 
-    let mut seen = false;
-    solver.solve(|rows| {
-        rows.sort();
-        assert!(!seen);
-        assert_eq!(rows, &[0, 3, 4]);
-        seen = true;
-        core::ops::ControlFlow::<(), _>::Continue(())
-    });
+    /// Solve like [`Solver::solve`], but reuse the buffers in `scratch`
+    /// instead of allocating fresh ones.
+    ///
+    /// Once `scratch` has grown to fit the deepest solution, searches
+    /// without column multiplicities allocate nothing, so one scratch can
+    /// serve many solves, e.g. over clones of the same solver.
+    pub fn solve_in<T, F: FnMut(&mut [usize]) -> ControlFlow<T, ()>>(
+        &self,
+        scratch: &mut SolveScratch,
+        mut inspect: F,
+    ) -> Option<T> {
+        let SolveScratch { solution, buffer } = scratch;
+        solution.clear();
+        self.solve_inner(
+            &self.heuristic(),
+            &mut |_| false,
+            solution,
+            &mut |solution| {
+                buffer.clear();
+                buffer.extend(
+                    solution
+                        .iter()
+                        .map(|index| usize::from(self.matrix[*index].row)),
+                );
+                inspect(buffer)
+            },
+        )
+    }
+
+    /// Solve, additionally returning statistics about the search.
+    pub fn solve_with_stats<T, F: FnMut(&mut [usize]) -> ControlFlow<T, ()>>(
+        &self,
+        mut inspect: F,
+    ) -> (Option<T>, Stats) {
+        let heuristic = Counting {
+            inner: self.heuristic(),
+            count: Cell::new(0),
+        };
+
+        let mut solutions = 0;
+        let out = self.solve_rows(
+            &heuristic,
+            |_| false,
+            &mut Vec::new(),
+            |solution| {
+                solutions += 1;
+                inspect(solution)
+            },
+        );
+
+        let stats = Stats {
+            nodes: heuristic.count.get() + solutions,
+            solutions,
+        };
+
+        (out, stats)
+    }
+
+    /// Solve, branching on the column picked by `heuristic` at each step
+    /// instead of the default [`MinRemaining`].
+    pub fn solve_with_heuristic<H: Heuristic, T, F: FnMut(&mut [usize]) -> ControlFlow<T, ()>>(
+        &self,
+        heuristic: &H,
+        inspect: F,
+    ) -> Option<T> {
+        self.solve_rows(heuristic, |_| false, &mut Vec::new(), inspect)
+    }
+
+    /// Solve like [`Solver::solve`], but in an order that depends only on
+    /// the rows, ignoring [`Solver::set_tiebreak`] and
+    /// [`Solver::new_ordered`].
+    ///
+    /// The search branches on the column with the fewest remaining rows,
+    /// breaking ties by least sparse ID, and tries that column's rows in
+    /// increasing index. Each solution lists rows in the order they were
+    /// selected. Sparse IDs map to dense columns through a sorted `Vec`, so
+    /// no hashing is involved, and the same rows always produce the same
+    /// solutions in the same order.
+    pub fn solve_deterministic<T, F: FnMut(&mut [usize]) -> ControlFlow<T, ()>>(
+        &self,
+        inspect: F,
+    ) -> Option<T> {
+        let heuristic = MinRemainingBy(|a, b| self.sparse(a).cmp(&self.sparse(b)));
+        self.solve_rows(&heuristic, |_| false, &mut Vec::new(), inspect)
+    }
+
+    /// Solve, pruning any branch that would select more than `max_depth`
+    /// rows.
+    ///
+    /// Each level of the search selects one row, so this reports exactly
+    /// the solutions with at most `max_depth` rows. Branches cut off at the
+    /// limit are treated as dead ends, never as solutions.
+    pub fn solve_bounded_depth<T, F: FnMut(&mut [usize]) -> ControlFlow<T, ()>>(
+        &self,
+        max_depth: usize,
+        inspect: F,
+    ) -> Option<T> {
+        self.solve_rows(
+            &self.heuristic(),
+            |solution| solution.len() >= max_depth,
+            &mut Vec::new(),
+            inspect,
+        )
+    }
+
+    /// Solve, calling `on_node` with the rows selected so far at each
+    /// internal node of the search before branching.
+    ///
+    /// Returning [`ControlFlow::Break`] from `on_node` prunes the subtree
+    /// below that node, as a hook for branch-and-bound. `on_solution` is
+    /// called with each complete solution as in [`Solver::solve`].
+    pub fn solve_with_node_hook<
+        T,
+        N: FnMut(&[usize]) -> ControlFlow<()>,
+        F: FnMut(&mut [usize]) -> ControlFlow<T, ()>,
+    >(
+        &self,
+        mut on_node: N,
+        on_solution: F,
+    ) -> Option<T> {
+        let mut buffer = Vec::new();
+        self.solve_rows(
+            &self.heuristic(),
+            |solution| {
+                buffer.clear();
+                buffer.extend(
+                    solution
+                        .iter()
+                        .map(|index| usize::from(self.matrix[*index].row)),
+                );
+                on_node(&buffer).is_break()
+            },
+            &mut Vec::new(),
+            on_solution,
+        )
+    }
+
+    /// Estimate the number of solutions by averaging `samples` random
+    /// descents through the search tree, after Knuth's "Estimating the
+    /// efficiency of backtrack programs".
+    ///
+    /// Each descent picks a uniformly random row at every branch, and
+    /// contributes the product of the branching factors along its path if
+    /// it ends in a solution, or zero at a dead end. The result is an
+    /// unbiased estimate, not an exact count, and its variance can be large
+    /// when the tree is lopsided. `rng` should return uniformly random
+    /// `u64`s, e.g. `|| rng.next_u64()` with the `rand` crate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `samples` is zero, or if any column has an upper bound
+    /// above one.
+    pub fn estimate_solutions<R: FnMut() -> u64>(&self, samples: usize, mut rng: R) -> f64 {
+        assert!(samples > 0, "Estimate requires at least one sample");
+        self.assert_at_most_once("Estimate");
+
+        let heuristic = self.heuristic();
+        let mut path = Vec::new();
+        let mut total = 0.0;
+
+        for _ in 0..samples {
+            let mut weight = 1.0;
+
+            loop {
+                let mut candidates = self
+                    .matrix
+                    .walk_right(matrix::Index::GLOBAL)
+                    .map(|index| self.matrix.index_to_column(index))
+                    .map(|col| (col, self.matrix.size(col)))
+                    .peekable();
+
+                if candidates.peek().is_none() {
+                    total += weight;
+                    break;
+                }
+
+                let Some(col) = heuristic.choose(candidates) else {
+                    break;
+                };
+
+                let size = self.matrix.size(col);
+                if size == 0 {
+                    break;
+                }
+
+                weight *= size as f64;
+                self.cover(col);
+
+                let i = self
+                    .matrix
+                    .walk_down(col.into())
+                    .nth((rng() % size as u64) as usize)
+                    .expect("Column shorter than its size");
+
+                self.select(i);
+                path.push((col, i));
+            }
+
+            for (col, i) in path.drain(..).rev() {
+                self.deselect(i);
+                self.uncover(col);
+            }
+        }
+
+        total / samples as f64
+    }
+
+    /// Enumerate every solution, logging each change to the matrix along
+    /// the way.
+    ///
+    /// Unlike [`Solver::solve_trace`], this also records the columns covered
+    /// and uncovered by selecting each row, along with the rows removed by
+    /// each cover, so every mutation of the links is observable. Both the
+    /// solutions and the log are held in memory, so this is only suitable
+    /// for small instances.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any column has an upper bound above one.
+    pub fn solve_logged(&self) -> (Vec<Vec<usize>>, Vec<LogEntry>) {
+        self.assert_at_most_once("Logged search");
+
+        let mut solutions = Vec::new();
+        let mut log = Vec::new();
+        self.solve_logged_inner(&mut Vec::new(), &mut solutions, &mut log);
+        (solutions, log)
+    }
+
+    /// Solve, reporting every step of the search to `visit`.
+    ///
+    /// Each column chosen for branching is reported as [`Event::Cover`],
+    /// followed by an [`Event::Select`] for each row tried, and finally an
+    /// [`Event::Backtrack`] once its rows are exhausted. This is slower than
+    /// [`Solver::solve`], so prefer it unless the trace is needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any column has an upper bound above one.
+    pub fn solve_trace<T, F: FnMut(Event<'_>) -> ControlFlow<T, ()>>(
+        &self,
+        mut visit: F,
+    ) -> Option<T> {
+        self.assert_at_most_once("Traced search");
+        self.solve_trace_inner(&mut Vec::new(), &mut visit)
+    }
+
+    /// Solve, allowing up to `allow_uncovered` primary columns to be left
+    /// uncovered by the selected rows.
+    ///
+    /// Leaving a column uncovered is tried as one more branch alongside its
+    /// rows, so every partial cover with few enough holes is reported, and
+    /// their number grows combinatorially with `allow_uncovered`. Consider
+    /// returning [`ControlFlow::Break`] once enough solutions are found.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any column has an upper bound above one.
+    pub fn solve_partial<T, F: FnMut(&mut [usize]) -> ControlFlow<T, ()>>(
+        &self,
+        allow_uncovered: usize,
+        mut inspect: F,
+    ) -> Option<T> {
+        self.assert_at_most_once("Partial search");
+        let mut buffer = Vec::new();
+        self.solve_partial_inner(allow_uncovered, &mut Vec::new(), &mut |solution| {
+            buffer.clear();
+            buffer.extend_from_slice(solution);
+            inspect(&mut buffer)
+        })
+    }
+
+    /// Solve, handing the callback references into `rows` instead of
+    /// row indices. `rows` should be the same slice passed to [`Solver::new`].
+    pub fn solve_mapped<'a, T, R, F: FnMut(&[&'a R]) -> ControlFlow<T, ()>>(
+        &self,
+        rows: &'a [R],
+        mut inspect: F,
+    ) -> Option<T> {
+        let mut buffer = Vec::new();
+        self.solve(|solution| {
+            buffer.clear();
+            buffer.extend(solution.iter().map(|index| &rows[*index]));
+            inspect(&buffer)
+        })
+    }
+
+    /// Solve, handing the callback the [`Identified::id`] of each selected
+    /// row instead of its index. `rows` should be the same slice passed to
+    /// [`Solver::new`].
+    ///
+    /// Identifiers are computed once up front, and cloned into a buffer for
+    /// each solution.
+    pub fn solve_ids<R: Identified, T, F: FnMut(&[R::Id]) -> ControlFlow<T, ()>>(
+        &self,
+        rows: &[R],
+        mut inspect: F,
+    ) -> Option<T> {
+        let ids = rows.iter().map(Identified::id).collect::<Vec<_>>();
+        let mut buffer = Vec::new();
+        self.solve(|solution| {
+            buffer.clear();
+            buffer.extend(solution.iter().map(|index| ids[*index].clone()));
+            inspect(&buffer)
+        })
+    }
+
+    /// Return every solution with its row indices sorted, in lexicographic
+    /// order, independent of the order the search finds them in.
+    pub fn solutions_sorted(&self) -> Vec<Vec<usize>> {
+        let mut solutions = self.collect_solutions(usize::MAX);
+        solutions
+            .iter_mut()
+            .for_each(|solution| solution.sort_unstable());
+        solutions.sort_unstable();
+        solutions
+    }
+
+    /// Return the row indices of the first solution found, or `None` if
+    /// there are none.
+    pub fn solve_first(&self) -> Option<Vec<usize>> {
+        self.solve(|solution| ControlFlow::Break(solution.to_vec()))
+    }
+
+    /// Send the row indices of each solution over `tx` as it is found,
+    /// stopping early once the receiver is dropped.
+    #[cfg(feature = "std")]
+    pub fn solve_to_sender(&self, tx: std::sync::mpsc::Sender<Vec<usize>>) {
+        self.solve(|solution| match tx.send(solution.to_vec()) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(_) => ControlFlow::Break(()),
+        });
+    }
+
+    /// Return the row indices of the `n`th solution found, counting from
+    /// zero in search order, or `None` if there are at most `n` solutions.
+    ///
+    /// Earlier solutions are skipped without being copied.
+    pub fn solve_nth(&self, n: usize) -> Option<Vec<usize>> {
+        let mut seen = 0;
+        self.solve(|solution| match seen == n {
+            true => ControlFlow::Break(solution.to_vec()),
+            false => {
+                seen += 1;
+                ControlFlow::Continue(())
+            }
+        })
+    }
+
+    /// Return the row indices of up to `max` solutions, in search order.
+    pub fn collect_solutions(&self, max: usize) -> Vec<Vec<usize>> {
+        let mut solutions = Vec::new();
+
+        if max == 0 {
+            return solutions;
+        }
+
+        self.solve(|solution| {
+            solutions.push(solution.to_vec());
+            match solutions.len() < max {
+                true => ControlFlow::Continue(()),
+                false => ControlFlow::Break(()),
+            }
+        });
+
+        solutions
+    }
+
+    /// Solve with the rows in `fixed` selected up front, so that every
+    /// reported solution contains them.
+    ///
+    /// Fails without searching if any two fixed rows share a column.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any column has an upper bound above one.
+    pub fn solve_with_fixed<T, F: FnMut(&mut [usize]) -> ControlFlow<T, ()>>(
+        &self,
+        fixed: &[usize],
+        inspect: F,
+    ) -> Result<Option<T>, FixedError> {
+        self.assert_at_most_once("Fixed search");
+        let heads = self.cover_fixed(fixed)?;
+
+        let mut solution = heads.clone();
+        let out = self.solve_rows(&self.heuristic(), |_| false, &mut solution, inspect);
+
+        self.uncover_fixed(&heads);
+        Ok(out)
+    }
+
+    /// Check whether selecting the rows in `fixed` leaves no row able to
+    /// cover the sparse column `target_col`, without running a search.
+    ///
+    /// Returns `false` if `target_col` is covered by one of the fixed rows.
+    /// Fails if `target_col` is not a column of this solver, or for the
+    /// same fixed rows as [`Solver::solve_with_fixed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if any column has an upper bound above one.
+    pub fn would_orphan(&self, fixed: &[usize], target_col: u32) -> Result<bool, FixedError> {
+        self.assert_at_most_once("Orphan check");
+
+        let dense = self
+            .columns
+            .binary_search(&target_col)
+            .map_err(|_| FixedError::UnknownColumn(target_col))?;
+
+        let col = self.matrix.column(dense as u32 + 1);
+        let heads = self.cover_fixed(fixed)?;
+
+        let orphaned = heads
+            .iter()
+            .all(|head| self.row_columns(*head).all(|covered| covered != col))
+            && self.matrix.size(col) == 0;
+
+        self.uncover_fixed(&heads);
+        Ok(orphaned)
+    }
+
+    /// Cover the columns of each row in `fixed`, returning their first nodes.
+    ///
+    /// The matrix is left untouched on error.
+    fn cover_fixed(&self, fixed: &[usize]) -> Result<Vec<matrix::Index>, FixedError> {
+        let mut owners = BTreeMap::new();
+        let mut heads = Vec::with_capacity(fixed.len());
+
+        for &row in fixed {
+            let head = match self.rows.get(row) {
+                None => return Err(FixedError::OutOfBounds(row)),
+                Some(&matrix::Index::DANGLING) => return Err(FixedError::Empty(row)),
+                Some(_) if self.disabled.contains(&row) => return Err(FixedError::Disabled(row)),
+                Some(head) => *head,
+            };
+
+            for col in self.row_columns(head) {
+                if let Some(other) = owners.insert(col, row) {
+                    return Err(FixedError::Conflict(other, row));
+                }
+            }
+
+            heads.push(head);
+        }
+
+        for head in &heads {
+            self.row_columns(*head).for_each(|col| self.cover(col));
+        }
+
+        Ok(heads)
+    }
+
+    // Guard entry points that cover each column outright, ignoring bounds.
+    fn assert_at_most_once(&self, method: &str) {
+        assert!(
+            self.multiplicity.is_none(),
+            "{} requires columns to be covered at most once",
+            method,
+        );
+    }
+
+    fn uncover_fixed(&self, heads: &[matrix::Index]) {
+        for head in heads.iter().rev() {
+            self.row_columns(*head)
+                .collect::<Vec<_>>()
+                .into_iter()
+                .rev()
+                .for_each(|col| self.uncover(col));
+        }
+    }
+
+    /// Solve as if the rows in `excluded` did not exist.
+    ///
+    /// The excluded rows are unlinked from their columns for the duration
+    /// of the search and restored before returning.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some row in `excluded` does not exist, before unlinking any.
+    pub fn solve_excluding<T, F: FnMut(&mut [usize]) -> ControlFlow<T, ()>>(
+        &self,
+        excluded: &[usize],
+        inspect: F,
+    ) -> Option<T> {
+        let heads = excluded
+            .iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter(|row| !self.disabled.contains(row))
+            .map(|row| self.rows[*row])
+            .filter(|head| *head != matrix::Index::DANGLING)
+            .collect::<Vec<_>>();
+
+        heads.iter().for_each(|head| self.detach_row(*head));
+        let out = self.solve(inspect);
+        heads.iter().rev().for_each(|head| self.reattach_row(*head));
+
+        out
+    }
+
+    /// Solve, reporting only solutions that select at least one row from
+    /// each of `groups`.
+    ///
+    /// Rather than filtering complete solutions, each group becomes a new
+    /// primary column covered by every row in the group, with bounds of one
+    /// up to the group's size. The search then branches on a group as soon
+    /// as it runs short of rows, and prunes any branch that leaves a group
+    /// unsatisfiable. This builds a new matrix on every call.
+    ///
+    /// Unlike [`Solver::solve`], this may select rows covering only
+    /// secondary columns, if they belong to some group.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some row index is out of bounds, or if there are too many
+    /// groups to assign new sparse column IDs above the existing ones.
+    pub fn solve_requiring<T, F: FnMut(&mut [usize]) -> ControlFlow<T, ()>>(
+        &self,
+        groups: &[Vec<usize>],
+        inspect: F,
+    ) -> Option<T> {
+        let groups = groups
+            .iter()
+            .map(|group| group.iter().copied().collect::<BTreeSet<_>>())
+            .collect::<Vec<_>>();
+
+        // No selection can satisfy an empty group
+        if groups.iter().any(BTreeSet::is_empty) {
+            return None;
+        }
+
+        let mut bounds = self.sparse_bounds();
+        let mut rows = self.sparse_rows();
+
+        let next = self.columns.last().map_or(0, |sparse| sparse + 1);
+        for (index, group) in groups.iter().enumerate() {
+            let sparse = u32::try_from(index)
+                .ok()
+                .and_then(|index| next.checked_add(index))
+                .expect("Too many groups to encode in 32-bit columns");
+
+            bounds.insert(sparse, (1, group.len().min(u16::MAX as usize) as u16));
+            for row in group {
+                rows[*row].0.push(sparse);
+            }
+        }
+
+        let mut solver = Self::with_multiplicity(&rows, |sparse| bounds[&sparse]);
+        solver.tiebreak = self.tiebreak.clone();
+        for row in &self.disabled {
+            solver.disable_row(*row);
+        }
+        solver.solve(inspect)
+    }
+
+    /// Partition the matrix into independent subproblems, one per connected
+    /// component of rows linked by shared columns.
+    ///
+    /// The solution count of the whole problem is the product of the
+    /// components' solution counts. Each component numbers its rows in
+    /// increasing order of their index in this solver, omitting rows that
+    /// cover no columns, and is paired with the index in this solver of each
+    /// of its rows. Bounds, tiebreaks, and disabled rows carry over.
+    pub fn components(&self) -> Vec<(Self, Vec<usize>)> {
+        let rows = self.sparse_rows();
+
+        let mut by_column = BTreeMap::<u32, Vec<usize>>::new();
+        for (index, row) in rows.iter().enumerate() {
+            for sparse in &row.0 {
+                by_column.entry(*sparse).or_default().push(index);
+            }
+        }
+
+        let bounds = self.sparse_bounds();
+        let mut seen = vec![false; rows.len()];
+        let mut components = Vec::new();
+
+        for start in 0..rows.len() {
+            if seen[start] || rows[start].0.is_empty() {
+                continue;
+            }
+
+            seen[start] = true;
+            let mut component = Vec::new();
+            let mut queue = alloc::collections::VecDeque::from([start]);
+
+            while let Some(row) = queue.pop_front() {
+                component.push(row);
+                for next in rows[row].0.iter().flat_map(|sparse| &by_column[sparse]) {
+                    if !seen[*next] {
+                        seen[*next] = true;
+                        queue.push_back(*next);
+                    }
+                }
+            }
+
+            component.sort_unstable();
+
+            let subrows = component
+                .iter()
+                .map(|row| rows[*row].clone())
+                .collect::<Vec<_>>();
+
+            let mut solver = Self::with_multiplicity(&subrows, |sparse| bounds[&sparse]);
+            solver.tiebreak = self.tiebreak.clone();
+            for (local, row) in component.iter().enumerate() {
+                if self.disabled.contains(row) {
+                    solver.disable_row(local);
+                }
+            }
+
+            components.push((solver, component));
+        }
+
+        components
+    }
+
+    // Bounds of each sparse column, as passed to `with_multiplicity`.
+    fn sparse_bounds(&self) -> BTreeMap<u32, (u16, u16)> {
+        let primary = self
+            .matrix
+            .walk_right(matrix::Index::GLOBAL)
+            .map(|index| self.matrix.index_to_column(index))
+            .collect::<BTreeSet<_>>();
+
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(dense, sparse)| {
+                let col = self.matrix.column(dense as u32 + 1);
+                let bounds = match &self.multiplicity {
+                    Some(multiplicity) => multiplicity.bounds(col),
+                    None if primary.contains(&col) => (1, 1),
+                    None => (0, 1),
+                };
+                (*sparse, bounds)
+            })
+            .collect()
+    }
+
+    // Sparse columns of each row, including disabled ones.
+    fn sparse_rows(&self) -> Vec<Columns> {
+        self.rows
+            .iter()
+            .map(|head| match *head {
+                matrix::Index::DANGLING => Columns(Vec::new()),
+                head => Columns(self.row_columns(head).map(|col| self.sparse(col)).collect()),
+            })
+            .collect()
+    }
+
+    fn detach_row(&self, head: matrix::Index) {
+        for index in iter::once(head).chain(self.matrix.walk_right(head)) {
+            self.matrix.detach_vertical(index);
+            self.matrix
+                .update_size(self.matrix.index_to_column(index), -1);
+        }
+    }
+
+    fn reattach_row(&self, head: matrix::Index) {
+        for index in iter::once(head).chain(self.matrix.walk_left(head)) {
+            self.matrix.reattach_vertical(index);
+            self.matrix
+                .update_size(self.matrix.index_to_column(index), 1);
+        }
+    }
+
+    fn row_columns(&self, head: matrix::Index) -> impl Iterator<Item = matrix::Col> + '_ {
+        iter::once(head)
+            .chain(self.matrix.walk_right(head))
+            .map(|index| self.matrix.index_to_column(index))
+    }
+
+    fn solve_rows<
+        H: Heuristic,
+        P: FnMut(&[matrix::Index]) -> bool,
+        T,
+        F: FnMut(&mut [usize]) -> ControlFlow<T, ()>,
+    >(
+        &self,
+        heuristic: &H,
+        mut prune: P,
+        solution: &mut Vec<matrix::Index>,
+        mut inspect: F,
+    ) -> Option<T> {
+        let mut buffer = Vec::new();
+        self.solve_inner(heuristic, &mut prune, solution, &mut |solution| {
+            buffer.clear();
+            buffer.extend(
+                solution
+                    .iter()
+                    .map(|index| usize::from(self.matrix[*index].row)),
+            );
+            inspect(&mut buffer)
+        })
+    }
+
+    /// Enumerate every solution and return the one with the lowest `score`,
+    /// where `tiles[i]` is the placed tile corresponding to row `i`.
+    ///
+    /// Ties are broken in favor of the first solution found. This is a full
+    /// enumeration, so it is only practical for small search spaces.
+    ///
+    /// Unlike [`Solver::solve_count_by_corners`], this takes no board
+    /// dimensions: the tiles are already placed in board coordinates, so
+    /// `score` can recover the extent of a solution from
+    /// [`tile::Set::bounding_box`] if it needs it.
+    pub fn solve_optimizing<const LEN: usize, S: Fn(&tile::Set<LEN>) -> i64>(
+        &self,
+        tiles: &[Tile<LEN>],
+        score: S,
+    ) -> Option<tile::Set<LEN>> {
+        let mut best = None::<(i64, tile::Set<LEN>)>;
+        self.solve(|solution| {
+            let set = solution
+                .iter()
+                .map(|index| tiles[*index])
+                .collect::<tile::Set<LEN>>();
+
+            let cost = score(&set);
+            if best.as_ref().map_or(true, |(min, _)| cost < *min) {
+                best = Some((cost, set));
+            }
+
+            ControlFlow::<(), ()>::Continue(())
+        });
+        best.map(|(_, set)| set)
+    }
+
+    /// Enumerate every solution and return the one minimizing `objective`,
+    /// along with its cost.
+    ///
+    /// Ties are broken in favor of the first solution found. Nothing is
+    /// pruned, so this is a full enumeration; see
+    /// [`Solver::solve_optimal_bounded`] to cut off hopeless branches.
+    pub fn solve_optimal<C: Ord, F: Fn(&[usize]) -> C>(
+        &self,
+        objective: F,
+    ) -> Option<(C, Vec<usize>)> {
+        let mut best = None::<(C, Vec<usize>)>;
+        self.solve(|solution| {
+            let cost = objective(solution);
+            if best.as_ref().map_or(true, |(min, _)| cost < *min) {
+                best = Some((cost, solution.to_vec()));
+            }
+            ControlFlow::<(), ()>::Continue(())
+        });
+        best
+    }
+
+    /// Like [`Solver::solve_optimal`], but prune any partial solution whose
+    /// `lower_bound` is no better than the best cost found so far.
+    ///
+    /// `lower_bound` must be admissible: it may never exceed the
+    /// `objective` of any solution extending the partial solution it is
+    /// given, or the true optimum may be pruned.
+    pub fn solve_optimal_bounded<C: Ord, F: Fn(&[usize]) -> C, B: Fn(&[usize]) -> C>(
+        &self,
+        objective: F,
+        lower_bound: B,
+    ) -> Option<(C, Vec<usize>)> {
+        let best = core::cell::RefCell::new(None::<(C, Vec<usize>)>);
+        self.solve_with_node_hook(
+            |partial| match &*best.borrow() {
+                Some((min, _)) if lower_bound(partial) >= *min => ControlFlow::Break(()),
+                _ => ControlFlow::Continue(()),
+            },
+            |solution| {
+                let cost = objective(solution);
+                let mut best = best.borrow_mut();
+                if best.as_ref().map_or(true, |(min, _)| cost < *min) {
+                    *best = Some((cost, solution.to_vec()));
+                }
+                ControlFlow::<(), ()>::Continue(())
+            },
+        );
+        best.into_inner()
+    }
+
+    /// Return the fewest rows any solution uses, along with every solution
+    /// using that many, or `None` if there is no solution.
+    ///
+    /// Branches are pruned once they select as many rows as the smallest
+    /// solution found so far, but ties are kept.
+    pub fn solve_min_cardinality(&self) -> Option<(usize, Vec<Vec<usize>>)> {
+        let best = core::cell::RefCell::new(None::<(usize, Vec<Vec<usize>>)>);
+        self.solve_with_node_hook(
+            |partial| match &*best.borrow() {
+                Some((min, _)) if partial.len() >= *min => ControlFlow::Break(()),
+                _ => ControlFlow::Continue(()),
+            },
+            |solution| {
+                let mut best = best.borrow_mut();
+                match &mut *best {
+                    Some((min, solutions)) if solution.len() == *min => {
+                        solutions.push(solution.to_vec());
+                    }
+                    Some((min, _)) if solution.len() > *min => (),
+                    _ => *best = Some((solution.len(), vec![solution.to_vec()])),
+                }
+                ControlFlow::<(), ()>::Continue(())
+            },
+        );
+        best.into_inner()
+    }
+
+    /// Count the distinct keys produced by mapping each solution's rows
+    /// through `canon`, e.g. to a representative of its symmetry class.
+    ///
+    /// Every distinct key is held in memory; see
+    /// [`Solver::solve_count_canonical_incremental`] for a constant-memory
+    /// alternative when solutions are sets of tiles.
+    pub fn solve_count_canonical<K: Ord, F: Fn(&[usize]) -> K>(&self, canon: F) -> usize {
+        self.solve_canonical(canon).len()
+    }
+
+    /// Collect the distinct keys produced by mapping each solution's rows
+    /// through `canon`, e.g. to a representative of its symmetry class.
+    pub fn solve_canonical<K: Ord, F: Fn(&[usize]) -> K>(&self, canon: F) -> BTreeSet<K> {
+        let mut keys = BTreeSet::new();
+        self.solve(|solution| {
+            keys.insert(canon(solution));
+            ControlFlow::<(), ()>::Continue(())
+        });
+        keys
+    }
+
+    /// Count solutions distinct up to `board_symmetries`, where `to_set` maps
+    /// rows to the tiles they place.
+    ///
+    /// A solution is counted only if it is no greater than any of its images,
+    /// so exactly one representative of each orbit survives. Unlike collecting
+    /// canonical sets into a `BTreeSet`, only the current solution is held in
+    /// memory. Tiles are assumed to be anchored at the origin, since each
+    /// symmetry clamps its output.
+    ///
+    /// Partial solutions are checked too: once the tile covering the board's
+    /// first cell is placed, which is the least tile of any extension, a
+    /// branch is pruned if some image of the placed tiles has a smaller one.
+    pub fn solve_count_canonical_incremental<
+        const LEN: usize,
+        F: Fn(&[usize]) -> tile::Set<LEN>,
+    >(
+        &self,
+        to_set: F,
+        board_symmetries: &[fn(&tile::Set<LEN>) -> tile::Set<LEN>],
+    ) -> usize {
+        self.solve_count_canonical_incremental_with_stats(to_set, board_symmetries)
+            .0
+    }
+
+    /// Count like [`Solver::solve_count_canonical_incremental`], also
+    /// reporting the size of the pruned search tree. [`Stats::solutions`]
+    /// is the canonical count.
+    pub fn solve_count_canonical_incremental_with_stats<
+        const LEN: usize,
+        F: Fn(&[usize]) -> tile::Set<LEN>,
+    >(
+        &self,
+        to_set: F,
+        board_symmetries: &[fn(&tile::Set<LEN>) -> tile::Set<LEN>],
+    ) -> (usize, Stats) {
+        let board = to_set(&(0..self.rows.len()).collect::<Vec<_>>());
+        let first = board.iter().flat_map(Tile::cells).min();
+
+        // Each symmetry clamps its output, so a partial solution would be
+        // translated by its own extent. Placing it alongside the tiles at
+        // the board's extremes, then removing their images, keeps it in
+        // board coordinates.
+        let anchors = [
+            board
+                .iter()
+                .min_by_key(|tile| tile.cells().map(|point| point.i).min()),
+            board
+                .iter()
+                .max_by_key(|tile| tile.cells().map(|point| point.i).max()),
+            board
+                .iter()
+                .min_by_key(|tile| tile.cells().map(|point| point.j).min()),
+            board
+                .iter()
+                .max_by_key(|tile| tile.cells().map(|point| point.j).max()),
+        ]
+        .into_iter()
+        .flatten()
+        .copied()
+        .collect::<tile::Set<LEN>>();
+
+        let images = board_symmetries
+            .iter()
+            .map(|symmetry| (*symmetry, symmetry(&anchors)))
+            .collect::<Vec<_>>();
+
+        let image = |set: &tile::Set<LEN>, (symmetry, anchored): &(_, tile::Set<LEN>)| {
+            let symmetry: fn(&tile::Set<LEN>) -> tile::Set<LEN> = *symmetry;
+            // Keep both copies of any placed anchor, so one survives
+            let mut set = set.clone();
+            set.extend(anchors.iter().copied());
+            symmetry(&set).difference(anchored)
+        };
+
+        let heuristic = Counting {
+            inner: self.heuristic(),
+            count: Cell::new(0),
+        };
+
+        let mut buffer = Vec::new();
+        let mut leaves = 0;
+        let mut count = 0;
+
+        self.solve_rows(
+            &heuristic,
+            |solution| {
+                buffer.clear();
+                buffer.extend(
+                    solution
+                        .iter()
+                        .map(|index| usize::from(self.matrix[*index].row)),
+                );
+
+                let set = to_set(&buffer);
+                let Some(least) = set
+                    .iter()
+                    .next()
+                    .filter(|tile| tile.cells().next() == first)
+                else {
+                    return false;
+                };
+
+                // Every extension has a smaller image
+                images
+                    .iter()
+                    .any(|symmetry| image(&set, symmetry).iter().next() < Some(least))
+            },
+            &mut Vec::new(),
+            |solution| {
+                leaves += 1;
+                let set = to_set(solution);
+                if images.iter().all(|symmetry| set <= image(&set, symmetry)) {
+                    count += 1;
+                }
+                ControlFlow::<(), ()>::Continue(())
+            },
+        );
+
+        let stats = Stats {
+            nodes: heuristic.count.get() + leaves,
+            solutions: count as u64,
+        };
+
+        (count, stats)
+    }
+
+    /// Solve, handing the callback the piece ID and placed tile of each
+    /// selected row, where `tiles[i]` describes row `i`.
+    pub fn solve_placements<
+        const LEN: usize,
+        T,
+        F: FnMut(&[(usize, Tile<LEN>)]) -> ControlFlow<T, ()>,
+    >(
+        &self,
+        tiles: &[(usize, Tile<LEN>)],
+        mut inspect: F,
+    ) -> Option<T> {
+        let mut buffer = Vec::new();
+        self.solve(|solution| {
+            buffer.clear();
+            buffer.extend(solution.iter().map(|index| tiles[*index]));
+            inspect(&buffer)
+        })
+    }
+
+    /// Count solutions keyed by the piece occupying each corner of a `rows`
+    /// by `cols` board, where `tiles[i]` is the piece ID and placed tile of
+    /// row `i`.
+    ///
+    /// Corners are ordered top-left, top-right, bottom-left, bottom-right,
+    /// and are `None` if no piece covers them. A board with no rows or no
+    /// columns has no corners, so the map is empty without searching.
+    pub fn solve_count_by_corners<const LEN: usize>(
+        &self,
+        tiles: &[(usize, Tile<LEN>)],
+        rows: u16,
+        cols: u16,
+    ) -> BTreeMap<[Option<usize>; 4], usize> {
+        if rows == 0 || cols == 0 {
+            return BTreeMap::new();
+        }
+
+        let (rows, cols) = (rows as usize, cols as usize);
+
+        self.solve_grouped(|solution| {
+            let grid = grid(solution.iter().map(|index| &tiles[*index]), rows, cols);
+            [0, cols - 1, (rows - 1) * cols, rows * cols - 1].map(|cell| grid[cell])
+        })
+    }
+
+    /// Count solutions per key, where `key` maps each solution's rows to
+    /// the group it belongs to.
+    ///
+    /// Only one count per distinct key is held in memory, never the
+    /// solutions themselves.
+    pub fn solve_grouped<K: Ord, F: Fn(&[usize]) -> K>(&self, key: F) -> BTreeMap<K, usize> {
+        let mut counts = BTreeMap::new();
+        self.solve(|solution| {
+            *counts.entry(key(solution)).or_default() += 1;
+            ControlFlow::<(), ()>::Continue(())
+        });
+        counts
+    }
+
+    fn solve_inner<
+        H: Heuristic,
+        P: FnMut(&[matrix::Index]) -> bool,
+        T,
+        F: FnMut(&[matrix::Index]) -> ControlFlow<T, ()>,
+    >(
+        &self,
+        heuristic: &H,
+        prune: &mut P,
+        solution: &mut Vec<matrix::Index>,
+        inspect: &mut F,
+    ) -> Option<T> {
+        if let Some(multiplicity) = &self.multiplicity {
+            return self.solve_multiplicity(multiplicity, heuristic, prune, solution, inspect);
+        }
+
+        let mut candidates = self
+            .matrix
+            .walk_right(matrix::Index::GLOBAL)
+            .map(|index| self.matrix.index_to_column(index))
+            .map(|col| (col, self.matrix.size(col)))
+            .peekable();
+
+        if candidates.peek().is_none() {
+            match inspect(solution) {
+                ControlFlow::Continue(()) => return None,
+                ControlFlow::Break(out) => return Some(out),
+            }
+        }
+
+        // Let the caller cut off this subtree, e.g. once it is too deep
+        if prune(solution) {
+            return None;
+        }
+
+        let col = heuristic.choose(candidates)?;
+
+        self.cover(col);
+
+        let mut out = None;
+
+        for i in self.matrix.walk_down(col.into()) {
+            solution.push(i);
+            self.select(i);
+
+            out = self.solve_inner(heuristic, prune, solution, inspect);
+
+            self.deselect(i);
+            solution.pop();
+
+            // Unwind fully before breaking so the matrix is restored
+            if out.is_some() {
+                break;
+            }
+        }
+
+        self.uncover(col);
+        out
+    }
+
+    fn solve_trace_inner<T, F: FnMut(Event<'_>) -> ControlFlow<T, ()>>(
+        &self,
+        solution: &mut Vec<usize>,
+        visit: &mut F,
+    ) -> Option<T> {
+        // Unlike `MinRemaining`, cover empty columns so dead ends are reported
+        let Some(col) = self.min_column() else {
+            return visit(Event::Solution(solution)).break_value();
+        };
+
+        self.cover(col);
+
+        let mut out = visit(Event::Cover(self.sparse(col))).break_value();
+
+        for i in self.matrix.walk_down(col.into()) {
+            if out.is_some() {
+                break;
+            }
+
+            let row = usize::from(self.matrix[i].row);
+            solution.push(row);
+            self.select(i);
+
+            out = visit(Event::Select(row))
+                .break_value()
+                .or_else(|| self.solve_trace_inner(solution, visit));
+
+            self.deselect(i);
+            solution.pop();
+        }
+
+        self.uncover(col);
+        out.or_else(|| visit(Event::Backtrack).break_value())
+    }
+
+    fn solve_logged_inner(
+        &self,
+        solution: &mut Vec<usize>,
+        solutions: &mut Vec<Vec<usize>>,
+        log: &mut Vec<LogEntry>,
+    ) {
+        // Like `solve_trace`, cover empty columns so dead ends are logged
+        let Some(col) = self.min_column() else {
+            solutions.push(solution.clone());
+            log.push(LogEntry::Solution);
+            return;
+        };
+
+        self.cover_logged(col, log);
+
+        for i in self.matrix.walk_down(col.into()) {
+            let row = usize::from(self.matrix[i].row);
+            solution.push(row);
+            log.push(LogEntry::SelectRow { row });
+
+            for j in self.matrix.walk_right(i) {
+                self.cover_logged(self.matrix.index_to_column(j), log);
+            }
+
+            self.solve_logged_inner(solution, solutions, log);
+
+            for j in self.matrix.walk_left(i) {
+                self.uncover_logged(self.matrix.index_to_column(j), log);
+            }
+
+            solution.pop();
+        }
+
+        self.uncover_logged(col, log);
+    }
+
+    fn cover_logged(&self, col: matrix::Col, log: &mut Vec<LogEntry>) {
+        let removed_rows = self
+            .matrix
+            .walk_down(col.into())
+            .map(|index| usize::from(self.matrix[index].row))
+            .collect();
+
+        self.cover(col);
+        log.push(LogEntry::Cover {
+            col: self.sparse(col),
+            removed_rows,
+        });
+    }
+
+    fn uncover_logged(&self, col: matrix::Col, log: &mut Vec<LogEntry>) {
+        self.uncover(col);
+        log.push(LogEntry::Uncover {
+            col: self.sparse(col),
+        });
+    }
+
+    fn solve_partial_inner<T, F: FnMut(&[usize]) -> ControlFlow<T, ()>>(
+        &self,
+        allow_uncovered: usize,
+        solution: &mut Vec<usize>,
+        inspect: &mut F,
+    ) -> Option<T> {
+        // Empty columns may still be left uncovered, so don't prune them
+        let Some(col) = self.min_column() else {
+            return inspect(solution).break_value();
+        };
+
+        self.cover(col);
+
+        let mut out = None;
+
+        for i in self.matrix.walk_down(col.into()) {
+            solution.push(usize::from(self.matrix[i].row));
+            self.select(i);
+
+            out = self.solve_partial_inner(allow_uncovered, solution, inspect);
+
+            self.deselect(i);
+            solution.pop();
+
+            if out.is_some() {
+                break;
+            }
+        }
+
+        // Leave the column uncovered, hiding every row that would cover it
+        if out.is_none() && allow_uncovered > 0 {
+            out = self.solve_partial_inner(allow_uncovered - 1, solution, inspect);
+        }
+
+        self.uncover(col);
+        out
+    }
+
+    // Generalization of `solve_inner` to columns with arbitrary bounds.
+    //
+    // Rows are tried in order, and each is excluded from later branches to
+    // avoid visiting the same set of rows twice. Columns are covered once
+    // they reach their upper bound, and a final branch covers the chosen
+    // column early if its lower bound has been met.
+    fn solve_multiplicity<
+        H: Heuristic,
+        P: FnMut(&[matrix::Index]) -> bool,
+        T,
+        F: FnMut(&[matrix::Index]) -> ControlFlow<T, ()>,
+    >(
+        &self,
+        multiplicity: &Multiplicity,
+        heuristic: &H,
+        prune: &mut P,
+        solution: &mut Vec<matrix::Index>,
+        inspect: &mut F,
+    ) -> Option<T> {
+        let candidates = || {
+            self.matrix
+                .walk_right(matrix::Index::GLOBAL)
+                .map(|index| self.matrix.index_to_column(index))
+                .map(|col| (col, self.matrix.size(col)))
+        };
+
+        if candidates().next().is_none() {
+            match inspect(solution) {
+                ControlFlow::Continue(()) => return None,
+                ControlFlow::Break(out) => return Some(out),
+            }
+        }
+
+        if prune(solution) {
+            return None;
+        }
+
+        // Prune if some column can no longer meet its lower bound
+        if candidates().any(|(col, size)| size < multiplicity.deficit(col)) {
+            return None;
+        }
+
+        // Columns with no rows left but no deficit can only be closed, and
+        // heuristics may treat empty columns as dead ends, so close them first
+        let col = match candidates().find(|(_, size)| *size == 0) {
+            Some((col, _)) => col,
+            None => heuristic.choose(candidates())?,
+        };
+
+        let mut out = None;
+        let mut excluded = Vec::new();
+
+        loop {
+            let i = self.matrix.down(col.into());
+            if i == col.into() {
+                break;
+            }
+
+            solution.push(i);
+            self.detach_row(i);
+            excluded.push(i);
+
+            for j in self.row_columns(i) {
+                let count = multiplicity.count(j);
+                count.set(count.get() + 1);
+                if count.get() == multiplicity.bounds(j).1 {
+                    self.cover(j);
+                }
+            }
+
+            out = self.solve_multiplicity(multiplicity, heuristic, prune, solution, inspect);
+
+            for j in self
+                .matrix
+                .walk_left(i)
+                .chain(iter::once(i))
+                .map(|j| self.matrix.index_to_column(j))
+            {
+                let count = multiplicity.count(j);
+                if count.get() == multiplicity.bounds(j).1 {
+                    self.uncover(j);
+                }
+                count.set(count.get() - 1);
+            }
+
+            solution.pop();
+
+            if out.is_some() {
+                break;
+            }
+        }
+
+        if out.is_none() && multiplicity.deficit(col) == 0 {
+            self.cover(col);
+            out = self.solve_multiplicity(multiplicity, heuristic, prune, solution, inspect);
+            self.uncover(col);
+        }
+
+        for i in excluded.into_iter().rev() {
+            self.reattach_row(i);
+        }
+
+        out
+    }
+
+    // Cover the remaining columns of the row containing `i`, assuming
+    // the column of `i` itself is already covered.
+    fn select(&self, i: matrix::Index) {
+        for j in self
+            .matrix
+            .walk_right(i)
+            .map(|j| self.matrix.index_to_column(j))
+        {
+            self.cover(j);
+        }
+    }
+
+    fn deselect(&self, i: matrix::Index) {
+        for j in self
+            .matrix
+            .walk_left(i)
+            .map(|j| self.matrix.index_to_column(j))
+        {
+            self.uncover(j);
+        }
+    }
+
+    fn cover(&self, col: matrix::Col) {
+        let col = col.into();
+
+        self.matrix.detach_horizontal(col);
+
+        for i in self.matrix.walk_down(col) {
+            for j in self.matrix.walk_right(i) {
+                self.matrix.detach_vertical(j);
+
+                let col = self.matrix.index_to_column(j);
+                self.matrix.update_size(col, -1);
+            }
+        }
+    }
+
+    fn uncover(&self, col: matrix::Col) {
+        let col = col.into();
+
+        for i in self.matrix.walk_up(col) {
+            for j in self.matrix.walk_left(i) {
+                self.matrix.reattach_vertical(j);
+
+                let col = self.matrix.index_to_column(j);
+                self.matrix.update_size(col, 1);
+            }
+        }
+
+        self.matrix.reattach_horizontal(col);
+    }
+}
+
+/// Imperative alternative to implementing [`Row`] for use with [`Solver::new`].
+#[derive(Clone, Debug, Default)]
+pub struct SolverBuilder {
+    rows: Vec<Columns>,
+
+    // Column sets of which exactly `k` must be covered by added rows
+    cardinality: Vec<(BTreeSet<u32>, usize)>,
+}
+
+impl SolverBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a row covering `columns`, returning its row index.
+    pub fn add_row<I: IntoIterator<Item = u32>>(&mut self, columns: I) -> usize {
+        self.rows.push(Columns(columns.into_iter().collect()));
+        self.rows.len() - 1
+    }
+
+    /// Require that exactly `k` of `columns` are covered by the rows added
+    /// with [`SolverBuilder::add_row`], leaving the rest uncovered.
+    ///
+    /// On [`SolverBuilder::build`], each column gets a filler row covering
+    /// it and a fresh auxiliary column, which must be covered exactly
+    /// `columns.len() - k` times. Filler rows are numbered after all added
+    /// rows, so their indices appear in solutions but are easily skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics on build if `k` exceeds the number of distinct columns, or if
+    /// some column appears in more than one constraint.
+    pub fn add_cardinality_constraint<I: IntoIterator<Item = u32>>(
+        &mut self,
+        columns: I,
+        k: usize,
+    ) {
+        self.cardinality.push((columns.into_iter().collect(), k));
+    }
+
+    pub fn build(self) -> Solver {
+        if self.cardinality.is_empty() {
+            return Solver::new(&self.rows);
+        }
+
+        let mut rows = self.rows;
+        let mut bounds = BTreeMap::new();
+
+        let next = rows
+            .iter()
+            .flat_map(Row::iter)
+            .chain(
+                self.cardinality
+                    .iter()
+                    .flat_map(|(columns, _)| columns.iter().copied()),
+            )
+            .max()
+            .map_or(0, |sparse| sparse + 1);
+
+        let mut constrained = BTreeSet::new();
+        for (index, (columns, k)) in self.cardinality.iter().enumerate() {
+            assert!(
+                *k <= columns.len(),
+                "Cannot cover {} of {} columns",
+                k,
+                columns.len(),
+            );
+
+            for column in columns {
+                assert!(
+                    constrained.insert(*column),
+                    "Column {} appears in more than one constraint",
+                    column,
+                );
+            }
+
+            let slack = columns.len() - k;
+            if slack == 0 {
+                continue;
+            }
+
+            let aux = u32::try_from(index)
+                .ok()
+                .and_then(|index| next.checked_add(index))
+                .expect("Too many constraints to encode in 32-bit columns");
+
+            let slack = u16::try_from(slack).expect("Too many columns in constraint");
+            bounds.insert(aux, (slack, slack));
+            rows.extend(columns.iter().map(|column| Columns(vec![*column, aux])));
+        }
+
+        Solver::with_multiplicity(&rows, |sparse| {
+            bounds.get(&sparse).copied().unwrap_or((1, 1))
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Columns(Vec<u32>);
+
+impl Row for Columns {
+    fn iter(&self) -> impl Iterator<Item = u32> {
+        self.0.iter().copied()
+    }
+}
+
+/// Reconstruct a row-major `rows` by `cols` grid labeling each cell with the
+/// ID of the piece covering it.
+fn grid<'a, const LEN: usize, I: IntoIterator<Item = &'a (usize, Tile<LEN>)>>(
+    placements: I,
+    rows: usize,
+    cols: usize,
+) -> Vec<Option<usize>> {
+    let mut grid = vec![None; rows * cols];
+
+    for (id, tile) in placements {
+        for point in tile.as_ref() {
+            grid[point.i as usize * cols + point.j as usize] = Some(*id);
+        }
+    }
+
+    grid
+}
+
+/// Reusable buffers for [`Solver::solve_in`].
+#[derive(Clone, Debug, Default)]
+pub struct SolveScratch {
+    solution: Vec<matrix::Index>,
+    buffer: Vec<usize>,
+}
+
+impl SolveScratch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Statistics reported by [`Solver::solve_with_stats`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of nodes in the search tree, including solutions and dead ends.
+    pub nodes: u64,
+    /// Number of solutions reported to the callback.
+    pub solutions: u64,
+}
+
+struct DefaultHeuristic<'a>(
+    Option<&'a (dyn Fn(matrix::Col, matrix::Col) -> Ordering + Send + Sync)>,
+);
+
+impl Heuristic for DefaultHeuristic<'_> {
+    fn choose<I: Iterator<Item = (matrix::Col, u32)>>(&self, candidates: I) -> Option<matrix::Col> {
+        match self.0 {
+            None => MinRemaining.choose(candidates),
+            Some(tiebreak) => MinRemainingBy(tiebreak).choose(candidates),
+        }
+    }
+}
+
+// Count the interior nodes of the search tree, where a column is chosen.
+struct Counting<H> {
+    inner: H,
+    count: Cell<u64>,
+}
+
+impl<H: Heuristic> Heuristic for Counting<H> {
+    fn choose<I: Iterator<Item = (matrix::Col, u32)>>(&self, candidates: I) -> Option<matrix::Col> {
+        self.count.set(self.count.get() + 1);
+        self.inner.choose(candidates)
+    }
+}
+
+/// Step of the search reported by [`Solver::solve_trace`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Event<'a> {
+    /// Covered the column with this sparse ID to branch on its rows.
+    Cover(u32),
+    /// Selected this row and descended one level.
+    Select(usize),
+    /// Exhausted the rows of the most recently covered column and uncovered it.
+    Backtrack,
+    /// Found a solution, given as row indices.
+    Solution(&'a [usize]),
+}
+
+/// Change to the matrix recorded by [`Solver::solve_logged`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LogEntry {
+    /// Covered the column with this sparse ID, unlinking the rows that
+    /// intersect it from every other column.
+    Cover { col: u32, removed_rows: Vec<usize> },
+    /// Uncovered the column with this sparse ID, relinking its rows.
+    Uncover { col: u32 },
+    /// Selected this row, about to cover its remaining columns.
+    SelectRow { row: usize },
+    /// Found a solution, the next in the returned list.
+    Solution,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FixedError {
+    /// Row index does not exist.
+    OutOfBounds(usize),
+    /// Row has no columns, so it can never be part of a solution.
+    Empty(usize),
+    /// Two fixed rows share a column.
+    Conflict(usize, usize),
+    /// Row is disabled, so it can never be part of a solution.
+    Disabled(usize),
+    /// Sparse column does not appear in any row.
+    UnknownColumn(u32),
+}
+
+impl Display for FixedError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FixedError::OutOfBounds(row) => write!(f, "Fixed row {} is out of bounds", row),
+            FixedError::Empty(row) => write!(f, "Fixed row {} has no columns", row),
+            FixedError::Conflict(a, b) => {
+                write!(f, "Fixed rows {} and {} share a column", a, b)
+            }
+            FixedError::Disabled(row) => write!(f, "Fixed row {} is disabled", row),
+            FixedError::UnknownColumn(col) => write!(f, "Column {} does not exist", col),
+        }
+    }
+}
+
+impl core::error::Error for FixedError {}
+
+/// Error returned by [`Solver::from_text`], with 1-based line numbers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParseInstanceError {
+    /// `primary` line doesn't give a column count.
+    InvalidPrimary { line: usize },
+    /// Token isn't a sparse column ID.
+    InvalidColumn { line: usize },
+    /// Row lists the same column more than once.
+    DuplicateColumn { line: usize, column: u32 },
+}
+
+impl Display for ParseInstanceError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseInstanceError::InvalidPrimary { line } => {
+                write!(f, "Invalid primary column count on line {}", line)
+            }
+            ParseInstanceError::InvalidColumn { line } => {
+                write!(f, "Invalid column on line {}", line)
+            }
+            ParseInstanceError::DuplicateColumn { line, column } => {
+                write!(f, "Duplicate column {} on line {}", column, line)
+            }
+        }
+    }
+}
+
+impl core::error::Error for ParseInstanceError {}
+
+#[test]
+fn smoke() {
+    struct Row(u8);
+
+    impl crate::solve::Row for Row {
+        fn iter(&self) -> impl Iterator<Item = u32> {
+            (0..8).filter(|bit| (self.0 >> bit) & 1 > 0)
+        }
+    }
+
+    let solver = Solver::new(&[
+        Row(0b0110100),
+        Row(0b1001001),
+        Row(0b0100110),
+        Row(0b0001001),
+        Row(0b1000010),
+        Row(0b1011000),
+    ]);
+
+    let mut seen = false;
+    solver.solve(|rows| {
+        rows.sort();
+        assert!(!seen);
+        assert_eq!(rows, &[0, 3, 4]);
+        seen = true;
+        core::ops::ControlFlow::<(), _>::Continue(())
+    });
+}
+
+#[test]
+fn builder() {
+    let mut builder = SolverBuilder::new();
+
+    for row in [
+        0b0110100u8,
+        0b1001001,
+        0b0100110,
+        0b0001001,
+        0b1000010,
+        0b1011000,
+    ] {
+        builder.add_row((0..8).filter(|bit| (row >> bit) & 1 > 0));
+    }
+
+    assert_eq!(builder.add_row([]), 6);
+
+    let mut seen = false;
+    builder.build().solve(|rows| {
+        rows.sort();
+        assert!(!seen);
+        assert_eq!(rows, &[0, 3, 4]);
+        seen = true;
+        core::ops::ControlFlow::<(), _>::Continue(())
+    });
+    assert!(seen);
+}
+
+#[test]
+fn orphan() {
+    let solver = Solver::new(&[Bits(0b011), Bits(0b110), Bits(0b100), Bits(0b101)]);
+
+    // Column 0 is covered by rows 0 and 3
+    assert_eq!(solver.would_orphan(&[], 0), Ok(false));
+    assert_eq!(solver.would_orphan(&[2], 0), Ok(false));
+    assert_eq!(solver.would_orphan(&[1], 0), Ok(true));
+
+    // Fixed rows covering the target itself don't orphan it
+    assert_eq!(solver.would_orphan(&[0], 0), Ok(false));
+    assert_eq!(solver.would_orphan(&[0], 2), Ok(false));
+    assert_eq!(solver.would_orphan(&[3], 1), Ok(true));
+
+    // Bad hints are errors rather than orphans
+    assert_eq!(
+        solver.would_orphan(&[], 3),
+        Err(FixedError::UnknownColumn(3))
+    );
+    assert_eq!(
+        solver.would_orphan(&[0, 1], 2),
+        Err(FixedError::Conflict(0, 1))
+    );
+    assert_eq!(
+        solver.would_orphan(&[4], 0),
+        Err(FixedError::OutOfBounds(4))
+    );
+
+    let mut disabled = solver.clone();
+    disabled.disable_row(2);
+    assert_eq!(disabled.would_orphan(&[2], 0), Err(FixedError::Disabled(2)));
+
+    assert_eq!(solver.solve_count(), 1);
+}
+
+#[test]
+fn counts() {
+    let solver = Solver::new(&[Bits(0b1001), Bits(0), Bits(0b1000), Bits(0b10)]);
+    assert_eq!(solver.len(), 4);
+    assert_eq!(solver.row_count(), 4);
+    assert_eq!(solver.column_count(), 3);
+}
+
+#[test]
+fn preliminary() {
+    let solver = Solver::new(&[Bits(0b011), Bits(0b110), Bits(0b100)]);
+    assert!(solver.is_satisfiable_preliminary());
+
+    solver.detach_row(solver.rows[0]);
+    assert!(!solver.is_satisfiable_preliminary());
+    assert_eq!(solver.uncoverable_columns(), [0]);
+
+    solver.reattach_row(solver.rows[0]);
+    assert!(solver.is_satisfiable_preliminary());
+}
+
+#[test]
+fn sizes() {
+    let solver = example();
+    assert_eq!(solver.column_sizes(), [(0, 3), (1, 3), (2, 3), (3, 3)]);
+
+    solver.solve(|_| {
+        assert!(solver.column_sizes().is_empty());
+        core::ops::ControlFlow::<(), _>::Continue(())
+    });
+    assert_eq!(solver.column_sizes(), [(0, 3), (1, 3), (2, 3), (3, 3)]);
+
+    let solver = Solver::with_secondary(&[Bits(0b011), Bits(0b110)], |col| col == 2);
+    assert_eq!(solver.column_sizes(), [(0, 1), (1, 2)]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn instance() {
+    let solver = Solver::new(&[Bits(0b0011), Bits(0), Bits(0b1100), Bits(0b0100)]);
+
+    let mut buffer = Vec::new();
+    solver.write_instance(&mut buffer).unwrap();
+    assert_eq!(
+        alloc::string::String::from_utf8_lossy(&buffer),
+        "4 4\n0 1\n\n2 3\n2\n"
+    );
+
+    let read = Solver::read_instance(&buffer[..]).unwrap();
+    assert_eq!(read.row_count(), 4);
+    assert_eq!(read.column_count(), 4);
+    assert_eq!(read.solve_count(), 1);
+
+    assert!(Solver::read_instance(&b"3 4\n0 1\n2 3\n"[..]).is_err());
+    assert!(Solver::read_instance(&b"1 2\n0 x\n"[..]).is_err());
+    assert!(Solver::read_instance(&b""[..]).is_err());
+    assert!(Solver::read_instance(&b"1 1\n0 0\n"[..]).is_err());
+}
+
+#[test]
+fn components() {
+    // Two independent blocks over columns {0, 1} and {4, 5, 6}, plus an
+    // empty row belonging to neither
+    let rows = [
+        Bits(0b0000011),
+        Bits(0b1110000),
+        Bits(0b0000001),
+        Bits(0),
+        Bits(0b0010000),
+        Bits(0b0000010),
+        Bits(0b1100000),
+        Bits(0b0110000),
+        Bits(0b1000000),
+    ];
+
+    let solver = Solver::new(&rows);
+    let components = solver
+        .components()
+        .into_iter()
+        .map(|(component, _)| component)
+        .collect::<Vec<_>>();
+    assert_eq!(components.len(), 2);
+    assert_eq!(components[0].row_count(), 3);
+    assert_eq!(components[1].row_count(), 5);
+    assert_eq!(components[0].solve_count(), 2);
+    assert_eq!(components[1].solve_count(), 3);
+    assert_eq!(
+        components
+            .iter()
+            .map(Solver::solve_count)
+            .product::<usize>(),
+        solver.solve_count(),
+    );
+
+    // Combining component solutions through their row maps gives back
+    // every joint solution
+    let mut solutions = vec![Vec::new()];
+    for (component, map) in solver.components() {
+        let mut combined = Vec::new();
+        for local in component.collect_solutions(usize::MAX) {
+            for partial in &solutions {
+                let mut rows = partial.clone();
+                rows.extend(local.iter().map(|row| map[*row]));
+                rows.sort_unstable();
+                combined.push(rows);
+            }
+        }
+        solutions = combined;
+    }
+    assert_eq!(
+        solutions.into_iter().collect::<BTreeSet<_>>(),
+        solver
+            .solutions_sorted()
+            .into_iter()
+            .collect::<BTreeSet<_>>(),
+    );
+
+    let mut solver = solver;
+    solver.disable_row(0);
+    let components = solver
+        .components()
+        .into_iter()
+        .map(|(component, _)| component)
+        .collect::<Vec<_>>();
+    assert_eq!(components[0].solve_count(), 1);
+    assert_eq!(
+        components
+            .iter()
+            .map(Solver::solve_count)
+            .product::<usize>(),
+        solver.solve_count(),
+    );
+
+    assert!(Solver::new::<Bits>(&[]).components().is_empty());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn send() {
+    let solver = Solver::new(&[Bits(0b01), Bits(0b10), Bits(0b11)]);
+    let count = std::thread::spawn(move || solver.solve_count())
+        .join()
+        .unwrap();
+    assert_eq!(count, 2);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn sender() {
+    const ROWS: [Bits; 5] = [Bits(0b01), Bits(0b10), Bits(0b11), Bits(0b01), Bits(0b10)];
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || Solver::new(&ROWS).solve_to_sender(tx))
+        .join()
+        .unwrap();
+
+    let mut solutions = rx.iter().collect::<Vec<_>>();
+    solutions.iter_mut().for_each(|solution| solution.sort());
+    solutions.sort();
+    assert_eq!(
+        solutions,
+        [vec![0, 1], vec![0, 4], vec![1, 3], vec![2], vec![3, 4]],
+    );
+
+    // Dropping the receiver stops the search instead of panicking
+    let (tx, rx) = std::sync::mpsc::channel();
+    let producer = std::thread::spawn(move || Solver::new(&ROWS).solve_to_sender(tx));
+    assert!(rx.recv().is_ok());
+    drop(rx);
+    producer.join().unwrap();
+}
+
+#[test]
+fn from_text() {
+    let solver = Solver::from_text("0 1\n\n  2 3\n2\n").unwrap();
+    assert_eq!(solver.row_count(), 3);
+    assert_eq!(solver.column_count(), 4);
+    assert_eq!(solver.solve_count(), 1);
+
+    // Column 2 is secondary, so row 1 alone is a solution
+    let solver = Solver::from_text("primary 2\n0 1\n0 1 2\n2\n").unwrap();
+    assert_eq!(solver.uncoverable_columns(), []);
+    assert_eq!(solver.column_sizes(), [(0, 2), (1, 2)]);
+    assert_eq!(solver.solve_count(), 2);
+
+    assert_eq!(Solver::from_text("").unwrap().solve_count(), 1);
+    assert_eq!(
+        Solver::from_text("primary\n0").err(),
+        Some(ParseInstanceError::InvalidPrimary { line: 1 }),
+    );
+    assert_eq!(
+        Solver::from_text("0\n\n1 x").err(),
+        Some(ParseInstanceError::InvalidColumn { line: 3 }),
+    );
+    assert_eq!(
+        Solver::from_text("1 0 1").err(),
+        Some(ParseInstanceError::DuplicateColumn { line: 1, column: 1 }),
+    );
+}
+
+#[test]
+fn render() {
+    let empty = Solver::new::<Bits>(&[]);
+    assert_eq!(alloc::format!("{:?}", empty.matrix), "");
+    assert_eq!(alloc::format!("{}", empty.matrix), "  \n  \n");
+
+    let solver = Solver::new(&[Bits(0b011), Bits(0b110), Bits(0b100)]);
+    assert_eq!(alloc::format!("{:?}", solver.matrix), "XX.\n.XX\n..X\n");
+
+    solver.cover(solver.matrix.column(1));
+    assert_eq!(alloc::format!("{:?}", solver.matrix), "...\n.XX\n..X\n");
+    assert_eq!(
+        alloc::format!("{}", solver.matrix),
+        "  012\n  -++\n0 xx.\n1 .XX\n2 ..X\n",
+    );
+    solver.uncover(solver.matrix.column(1));
+}
+
+#[test]
+fn collect() {
+    let solver = example();
+    assert!(solver.collect_solutions(0).is_empty());
+    assert_eq!(solver.collect_solutions(2).len(), 2);
+
+    let all = solver.solutions_sorted();
+    assert_eq!(all, [[0, 1], [2, 3], [4, 5]]);
+    assert_eq!(solver.collect_solutions(10).len(), 3);
+
+    let unsatisfiable = Solver::new(&[Bits(0b011), Bits(0b110)]);
+    assert!(unsatisfiable.collect_solutions(10).is_empty());
+
+    assert!(unsatisfiable.solve_first().is_none());
+    let mut first = solver.solve_first().unwrap();
+    first.sort();
+    assert!(all.contains(&first));
+}
+
+#[test]
+fn multiplicity() {
+    let rows = [Bits(0b01), Bits(0b01), Bits(0b11), Bits(0b10)];
+
+    let exact = Solver::with_multiplicity(&rows, |col| match col {
+        0 => (2, 2),
+        _ => (1, 1),
+    });
+    let mut solutions = exact.collect_solutions(usize::MAX);
+    solutions.iter_mut().for_each(|rows| rows.sort());
+    solutions.sort();
+    assert_eq!(solutions, [vec![0, 1, 3], vec![0, 2], vec![1, 2]]);
+
+    let slack = Solver::with_multiplicity(&rows, |col| match col {
+        0 => (1, 2),
+        _ => (1, 1),
+    });
+    assert_eq!(slack.solve_count(), 6);
+
+    // Counts and covers are restored after each search
+    assert_eq!(slack.solve_count(), 6);
+
+    let tight = Solver::with_multiplicity(&rows, |col| match col {
+        0 => (4, 4),
+        _ => (0, 1),
+    });
+    assert_eq!(tight.uncoverable_columns(), [0]);
+    assert_eq!(tight.solve_count(), 0);
+
+    assert_eq!(
+        Solver::with_multiplicity(&example_rows(), |_| (1, 1)).solve_count(),
+        example().solve_count(),
+    );
+}
+
+#[test]
+fn trace() {
+    let solver = Solver::new(&[Bits(0b01), Bits(0b10), Bits(0b11)]);
+
+    let mut events = Vec::new();
+    solver.solve_trace(|event| {
+        events.push(match event {
+            Event::Solution(rows) => alloc::format!("{:?}", rows),
+            event => alloc::format!("{:?}", event),
+        });
+        ControlFlow::<(), _>::Continue(())
+    });
+
+    assert_eq!(
+        events,
+        [
+            "Cover(0)",
+            "Select(0)",
+            "Cover(1)",
+            "Select(1)",
+            "[0, 1]",
+            "Backtrack",
+            "Select(2)",
+            "[2]",
+            "Backtrack",
+        ],
+    );
+
+    let mut selected = 0;
+    let out = solver.solve_trace(|event| match event {
+        Event::Select(_) => {
+            selected += 1;
+            ControlFlow::Break(selected)
+        }
+        _ => ControlFlow::Continue(()),
+    });
+    assert_eq!(out, Some(1));
+    assert_eq!(solver.solve_count(), 2);
+}
+
+#[test]
+fn logged() {
+    let solver = Solver::new(&[Bits(0b01), Bits(0b10), Bits(0b11)]);
+    let (solutions, log) = solver.solve_logged();
+
+    assert_eq!(solutions, [vec![0, 1], vec![2]]);
+    assert_eq!(
+        log,
+        [
+            LogEntry::Cover {
+                col: 0,
+                removed_rows: vec![0, 2],
+            },
+            LogEntry::SelectRow { row: 0 },
+            LogEntry::Cover {
+                col: 1,
+                removed_rows: vec![1],
+            },
+            LogEntry::SelectRow { row: 1 },
+            LogEntry::Solution,
+            LogEntry::Uncover { col: 1 },
+            LogEntry::SelectRow { row: 2 },
+            LogEntry::Cover {
+                col: 1,
+                removed_rows: vec![1],
+            },
+            LogEntry::Solution,
+            LogEntry::Uncover { col: 1 },
+            LogEntry::Uncover { col: 0 },
+        ],
+    );
+    solver.check_invariants();
+}
+
+#[cfg(test)]
+fn multiplicity_example() -> Solver {
+    Solver::with_multiplicity(&[Bits(0b01), Bits(0b01)], |_| (1, 2))
+}
+
+#[test]
+#[should_panic(expected = "Logged search requires columns to be covered at most once")]
+fn logged_multiplicity() {
+    multiplicity_example().solve_logged();
+}
+
+#[test]
+#[should_panic(expected = "Search requires columns to be covered at most once")]
+fn search_multiplicity() {
+    Search::new(multiplicity_example());
+}
+
+#[test]
+#[should_panic(expected = "Fixed search requires columns to be covered at most once")]
+fn fixed_multiplicity() {
+    let _ = multiplicity_example().solve_with_fixed(&[0], |_| ControlFlow::<()>::Continue(()));
+}
+
+#[test]
+#[should_panic(expected = "Orphan check requires columns to be covered at most once")]
+fn would_orphan_multiplicity() {
+    let _ = multiplicity_example().would_orphan(&[0], 0);
+}
+
+#[test]
+#[should_panic(expected = "Traced search requires columns to be covered at most once")]
+fn trace_multiplicity() {
+    multiplicity_example().solve_trace(|_| ControlFlow::<()>::Continue(()));
+}
+
+#[test]
+#[should_panic(expected = "Partial search requires columns to be covered at most once")]
+fn partial_multiplicity() {
+    multiplicity_example().solve_partial(0, |_| ControlFlow::<()>::Continue(()));
+}
+
+#[test]
+fn cardinality() {
+    // Place exactly 3 of 5 optional monomino pieces, one per cell of a
+    // 1x3 strip, in any arrangement
+    let mut builder = SolverBuilder::new();
+    for piece in 0..5 {
+        for cell in 0..3 {
+            builder.add_row([cell, 3 + piece]);
+        }
+    }
+    builder.add_cardinality_constraint(3..8, 3);
+
+    let solver = builder.build();
+    assert_eq!(solver.row_count(), 20);
+
+    let mut solutions = 0;
+    solver.solve(|solution| {
+        let placed = solution.iter().filter(|row| **row < 15).count();
+        assert_eq!(placed, 3);
+        solutions += 1;
+        ControlFlow::<()>::Continue(())
+    });
+
+    // 5 choose 3 subsets of pieces, each in 3! arrangements
+    assert_eq!(solutions, 10 * 6);
+
+    let mut builder = SolverBuilder::new();
+    builder.add_row([0]);
+    builder.add_row([1]);
+    builder.add_cardinality_constraint([0, 1], 2);
+    assert_eq!(builder.build().solve_count(), 1);
+
+    let mut builder = SolverBuilder::new();
+    builder.add_row([0]);
+    builder.add_row([1]);
+    builder.add_cardinality_constraint([0, 1], 0);
+    assert_eq!(builder.build().solve_first(), Some(vec![2, 3]));
+}
+
+#[test]
+#[should_panic(expected = "Cannot cover 3 of 2 columns")]
+fn cardinality_exceeded() {
+    let mut builder = SolverBuilder::new();
+    builder.add_row([0, 1]);
+    builder.add_cardinality_constraint([0, 1, 1], 3);
+    builder.build();
+}
+
+#[test]
+#[should_panic(expected = "Row 1 contains column 3 more than once")]
+fn duplicate() {
+    let mut builder = SolverBuilder::new();
+    builder.add_row([0, 1]);
+    builder.add_row([2, 3, 3]);
+    builder.build();
+}
+
+#[test]
+fn add_row() {
+    let mut solver = Solver::new(&[Bits(0b011), Bits(0b100), Bits(0b110)]);
+    assert_eq!(solver.solve_count(), 1);
+
+    assert_eq!(solver.add_row([0]), 3);
+    assert_eq!(solver.add_row([]), 4);
+    assert_eq!(solver.solve_count(), 2);
+    assert_eq!(solver.row_count(), 5);
+
+    let fresh = Solver::new(&[Bits(0b011), Bits(0b100), Bits(0b110), Bits(0b001), Bits(0)]);
+    assert_eq!(
+        alloc::format!("{}", solver.matrix),
+        alloc::format!("{}", fresh.matrix),
+    );
+}
+
+#[test]
+fn partial() {
+    let rows = example_rows();
+    let solver = example();
+
+    // Enumerate sets of disjoint rows missing at most `allow` columns
+    let expected = |allow: u32| {
+        (0u32..1 << rows.len())
+            .filter_map(|subset| {
+                let chosen = (0..rows.len()).filter(|row| subset >> row & 1 > 0);
+                let union = chosen.clone().try_fold(0u8, |union, row| {
+                    (union & rows[row].0 == 0).then_some(union | rows[row].0)
+                })?;
+                (4 - union.count_ones() <= allow).then(|| chosen.collect::<Vec<_>>())
+            })
+            .collect::<BTreeSet<_>>()
+    };
+
+    for allow in 0..=4 {
+        let mut actual = BTreeSet::new();
+        solver.solve_partial(allow, |rows| {
+            rows.sort();
+            assert!(
+                actual.insert(rows.to_vec()),
+                "Duplicate solution {:?}",
+                rows
+            );
+            ControlFlow::<(), _>::Continue(())
+        });
+        assert_eq!(actual, expected(allow as u32));
+    }
+
+    assert_eq!(solver.solve_count(), 3);
+}
+
+#[test]
+fn columns() {
+    let solver = Solver::new(&[Bits(0b1000_0010), Bits(0b0010_0000)]);
+    assert_eq!(solver.column_count(), 3);
+    assert_eq!(
+        (0..3)
+            .map(|dense| solver.sparse_column(dense))
+            .collect::<Vec<_>>(),
+        [1, 5, 7],
+    );
+    assert_eq!(solver.dense_column(5), Some(1));
+    assert_eq!(solver.dense_column(0), None);
+}
+
+#[test]
+fn bounded_depth() {
+    // Solutions {0} and {1, 2} differ in size
+    let solver = Solver::new(&[Bits(0b11), Bits(0b01), Bits(0b10)]);
+    let count = |max_depth| {
+        let mut count = 0;
+        solver.solve_bounded_depth(max_depth, |_| {
+            count += 1;
+            ControlFlow::<()>::Continue(())
+        });
+        count
+    };
+
+    assert_eq!(count(0), 0);
+    assert_eq!(count(1), 1);
+    assert_eq!(count(2), 2);
+    assert_eq!(count(usize::MAX), solver.solve_count());
+}
+
+#[test]
+fn node_hook() {
+    let solver = Solver::new(&[Bits(0b11), Bits(0b01), Bits(0b10)]);
+
+    let mut nodes = Vec::new();
+    let mut solutions = Vec::new();
+    solver.solve_with_node_hook(
+        |partial| {
+            nodes.push(partial.to_vec());
+            ControlFlow::Continue(())
+        },
+        |solution| {
+            solutions.push(solution.to_vec());
+            ControlFlow::<()>::Continue(())
+        },
+    );
+    assert_eq!(nodes, [vec![], vec![1]]);
+    assert_eq!(solutions, [vec![0], vec![1, 2]]);
+
+    // Pruning below row 1 leaves only the single-row solution
+    let solutions = solver.solve_with_node_hook(
+        |partial| match partial.contains(&1) {
+            true => ControlFlow::Break(()),
+            false => ControlFlow::Continue(()),
+        },
+        |solution| ControlFlow::Break(solution.to_vec()),
+    );
+    assert_eq!(solutions, Some(vec![0]));
+
+    // One row leaves the column open to a second, so pruning every
+    // nonempty partial solution rules out all three solutions
+    let multiplicity = Solver::with_multiplicity(&[Bits(0b1), Bits(0b1)], |_| (1, 2));
+    let mut count = 0;
+    multiplicity.solve_with_node_hook(
+        |partial| match partial.is_empty() {
+            true => ControlFlow::Continue(()),
+            false => ControlFlow::Break(()),
+        },
+        |_| {
+            count += 1;
+            ControlFlow::<()>::Continue(())
+        },
+    );
+    assert_eq!(count, 0);
+    assert_eq!(multiplicity.solve_count(), 3);
+}
+
+#[test]
+fn slack() {
+    // Column 2 can't be covered without also covering column 1 twice
+    let rows = [Bits(0b011), Bits(0b110)];
+    assert_eq!(Solver::new(&rows).solve_count(), 0);
+
+    let solver = Solver::with_slack(&rows, |col| col == 2);
+    assert_eq!(solver.row_count(), 3);
+    assert_eq!(solver.solve_first(), Some(vec![0, 2]));
+    assert_eq!(solver.solve_count(), 1);
+
+    let solver = Solver::with_slack(&rows, |_| true);
+    assert_eq!(solver.row_count(), 5);
+    assert_eq!(solver.solve_count(), 3);
+}
+
+#[test]
+fn ordered() {
+    // Columns 0 and 1 tie, so the first branch decides the first solution
+    let rows = [Bits(0b01), Bits(0b10), Bits(0b11)];
+
+    let solver = Solver::new(&rows);
+    assert_eq!(solver.solve_first(), Some(vec![0, 1]));
+
+    let solver = Solver::new_ordered(&rows, [1]);
+    solver.check_invariants();
+    assert_eq!(solver.solve_first(), Some(vec![1, 0]));
+    assert_eq!(solver.solve_count(), 2);
+
+    // First-seen order of `[1, 0]` is column 1 before column 0
+    let rows = [Bits(0b10), Bits(0b01), Bits(0b11)];
+    let solver = Solver::new_ordered(&rows, rows.iter().flat_map(Row::iter));
+    solver.check_invariants();
+    assert_eq!(solver.solve_first(), Some(vec![0, 1]));
+    assert_eq!(Solver::new(&rows).solve_first(), Some(vec![1, 0]));
+}
+
+#[test]
+fn priority() {
+    // Columns 0 and 1 tie, so priority decides the first branch
+    let rows = [Bits(0b01), Bits(0b10), Bits(0b11)];
+
+    let mut solver = Solver::new(&rows);
+    solver.set_column_priority(vec![1, 0]);
+    assert_eq!(solver.solve_first(), Some(vec![1, 0]));
+
+    // Equal priorities fall back to the leftmost column
+    solver.set_column_priority(vec![0, 0]);
+    assert_eq!(solver.solve_first(), Some(vec![0, 1]));
+    assert_eq!(solver.solve_count(), 2);
+}
+
+#[test]
+fn deterministic() {
+    let collect = |solver: &Solver| {
+        let mut solutions = Vec::new();
+        solver.solve_deterministic(|solution| {
+            solutions.push(solution.to_vec());
+            core::ops::ControlFlow::<(), _>::Continue(())
+        });
+        solutions
+    };
+
+    // Lock the order: column 0 first, then its rows by index
+    let expected = vec![vec![0, 1], vec![3, 2], vec![4, 5]];
+    assert_eq!(collect(&example()), expected);
+
+    // Neither header order nor tiebreaks change it
+    let mut solver = Solver::new_ordered(&example_rows(), [3, 2, 1]);
+    assert_eq!(collect(&solver), expected);
+    solver.set_tiebreak(|a, b| b.cmp(&a));
+    assert_eq!(collect(&solver), expected);
+    assert_ne!(solver.solve_first(), Some(vec![0, 1]));
+}
+
+#[test]
+fn optimal() {
+    // Solutions {0, 1}, {2}, and {3} differ in weight
+    let rows = [Bits(0b01), Bits(0b10), Bits(0b11), Bits(0b11)];
+    let weights = [1, 1, 5, 3];
+    let solver = Solver::new(&rows);
+
+    let weight = |solution: &[usize]| solution.iter().map(|row| weights[*row]).sum::<u32>();
+    assert_eq!(solver.solve_optimal(weight), Some((2, vec![0, 1])));
+    assert_eq!(
+        solver.solve_optimal(|solution| core::cmp::Reverse(weight(solution))),
+        Some((core::cmp::Reverse(5), vec![2])),
+    );
+
+    // Weights are nonnegative, so the partial weight is a lower bound
+    assert_eq!(
+        solver.solve_optimal_bounded(weight, weight),
+        Some((2, vec![0, 1])),
+    );
+
+    // Once {2} is found with weight 1, extending {1} can't do better
+    let weights = [1, 1, 1];
+    let weight = |solution: &[usize]| solution.iter().map(|row| weights[*row]).sum::<u32>();
+    let solver = Solver::new(&[Bits(0b11), Bits(0b01), Bits(0b10)]);
+    assert_eq!(
+        solver.solve_optimal_bounded(weight, weight),
+        Some((1, vec![0])),
+    );
+
+    let none = Solver::new(&[Bits(0b011), Bits(0b110)]);
+    assert_eq!(none.solve_optimal(|_| 0), None);
+    assert_eq!(none.solve_optimal_bounded(|_| 0, |_| 0), None);
+}
+
+#[test]
+fn min_cardinality() {
+    // Solutions {0, 1}, {2}, and {3} tie at one row but not two
+    let rows = [Bits(0b01), Bits(0b10), Bits(0b11), Bits(0b11)];
+    let solver = Solver::new(&rows);
+    assert_eq!(
+        solver.solve_min_cardinality(),
+        Some((1, vec![vec![2], vec![3]])),
+    );
+    assert_eq!(solver.solve_count(), 3);
+
+    // Both rows are needed, but they overlap
+    let solver = Solver::new(&[Bits(0b011), Bits(0b110)]);
+    assert_eq!(solver.solve_min_cardinality(), None);
+}
+
+#[test]
+fn retain() {
+    let mut solver = Solver::new(&[Bits(0b011), Bits(0b001), Bits(0b010), Bits(0b100)]);
+    assert_eq!(solver.solve_count(), 2);
+
+    solver.retain(|row| row != 0);
+    solver.check_invariants();
+    assert!(solver.is_row_disabled(0));
+    assert_eq!(solver.column_sizes(), [(0, 1), (1, 1), (2, 1)]);
+    assert_eq!(solver.solve_first(), Some(vec![1, 2, 3]));
+
+    // Row 3 is the only one covering column 2
+    solver.retain(|row| row != 3);
+    solver.check_invariants();
+    assert_eq!(solver.uncoverable_columns(), [2]);
+    assert!(!solver.is_satisfiable_preliminary());
+    assert_eq!(solver.solve_count(), 0);
+
+    solver.enable_row(0);
+    solver.enable_row(3);
+    solver.check_invariants();
+    assert_eq!(solver.solve_count(), 2);
+}
+
+#[test]
+fn column_report() {
+    let mut solver = Solver::new(&[Bits(0b1001), Bits(0b0001), Bits(0)]);
+    assert_eq!(solver.column_report(), [(0, 2), (1, 1)]);
+    assert_eq!(solver.sparse_column(1), 3);
+
+    solver.disable_row(0);
+    assert_eq!(solver.column_report(), [(0, 2), (1, 1)]);
+    assert!(Solver::new::<Bits>(&[]).column_report().is_empty());
+}
+
+#[test]
+fn nth() {
+    let solver = Solver::new(&[Bits(0b01), Bits(0b01), Bits(0b01), Bits(0b10)]);
+    let solutions = solver.collect_solutions(usize::MAX);
+    assert_eq!(solutions.len(), 3);
+
+    for (n, solution) in solutions.into_iter().enumerate() {
+        assert_eq!(solver.solve_nth(n), Some(solution));
+    }
+    assert_eq!(solver.solve_nth(3), None);
+    assert_eq!(solver.solve_nth(usize::MAX), None);
+}
+
+#[test]
+fn count_while() {
+    let solver = Solver::new(&[Bits(0b01), Bits(0b01), Bits(0b01), Bits(0b10)]);
+    assert_eq!(solver.count_while(|_| true), 3);
+    assert_eq!(solver.count_while(|count| count < 2), 2);
+    assert_eq!(solver.count_while(|count| count < 5), 3);
+    assert_eq!(solver.count_while(|_| false), 0);
+}
+
+#[test]
+fn pack() {
+    assert_eq!(pack_coords(0, 0, 1), Some(0));
+    assert_eq!(pack_coords(2, 3, 32), Some(67));
+    assert_eq!(pack_coords(u32::MAX >> 5, 31, 32), Some(u32::MAX));
+    assert_eq!(pack_coords(1 << 27, 0, 32), None);
+    assert_eq!(pack_coords(0, 32, 32), None);
+    assert_eq!(pack_coords(0, 0, 0), None);
+}
+
+#[test]
+fn estimate() {
+    // Xorshift, to keep the test deterministic
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut rng = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    // Every path branches 2 then 3 ways, so every sample is exact
+    let uniform = Solver::new(&[Bits(0b01), Bits(0b01), Bits(0b10), Bits(0b10), Bits(0b10)]);
+    assert_eq!(uniform.estimate_solutions(10, &mut rng), 6.0);
+
+    let none = Solver::new(&[Bits(0b011), Bits(0b110)]);
+    assert_eq!(none.estimate_solutions(10, &mut rng), 0.0);
+
+    let solver = example();
+    let estimate = solver.estimate_solutions(10_000, &mut rng);
+    assert!((estimate - solver.solve_count() as f64).abs() < 0.5);
+}
+
+#[test]
+fn requiring() {
+    let rows = example_rows();
+    let solver = example();
+    let solutions = solver.solutions_sorted();
+
+    let requiring = |groups: &[Vec<usize>]| {
+        let mut actual = Vec::new();
+        solver.solve_requiring(groups, |rows| {
+            rows.sort();
+            actual.push(rows.to_vec());
+            ControlFlow::<()>::Continue(())
+        });
+        actual.sort();
+        actual
+    };
+
+    assert_eq!(requiring(&[]), solutions);
+    assert_eq!(requiring(&[vec![]]), Vec::<Vec<usize>>::new());
+
+    for row in 0..rows.len() {
+        for other in 0..rows.len() {
+            let expected = solutions
+                .iter()
+                .filter(|solution| solution.contains(&row) || solution.contains(&other))
+                .cloned()
+                .collect::<Vec<_>>();
+            assert_eq!(requiring(&[vec![row, other, row]]), expected);
+        }
+    }
+
+    // Groups can force rows that cover only secondary columns
+    let secondary = Solver::with_secondary(&rows, |col| col == 0);
+    assert!(!secondary.solutions_sorted().contains(&vec![4, 5]));
+
+    let mut actual = Vec::new();
+    secondary.solve_requiring(&[vec![4], vec![5, 1]], |rows| {
+        rows.sort();
+        actual.push(rows.to_vec());
+        ControlFlow::<()>::Continue(())
+    });
+    assert_eq!(actual, [vec![4, 5]]);
+}
+
+#[test]
+fn invariants() {
+    let rows = example_rows();
+    let solver = example();
+    solver.check_invariants();
+
+    solver.solve_trace(|_| {
+        solver.check_invariants();
+        ControlFlow::<()>::Continue(())
+    });
+    solver.check_invariants();
+
+    let slack = Solver::with_multiplicity(&rows, |col| match col {
+        0 => (1, 2),
+        _ => (0, 1),
+    });
+    slack.solve(|_| {
+        slack.check_invariants();
+        ControlFlow::<()>::Continue(())
+    });
+    slack.check_invariants();
+
+    solver.solve_excluding(&[0, 5], |_| {
+        solver.check_invariants();
+        ControlFlow::<()>::Continue(())
+    });
+    solver.solve_partial(2, |_| {
+        solver.check_invariants();
+        ControlFlow::<()>::Continue(())
+    });
+    solver.check_invariants();
+}
+
+#[test]
+fn disable() {
+    let solver = example();
+    let mut variant = solver.clone();
+
+    assert!(variant.disable_row(0));
+    assert!(variant.disable_row(4));
+    assert!(variant.disable_row(3));
+    assert!(!variant.disable_row(4));
+    assert!(variant.is_row_disabled(4));
+    variant.check_invariants();
+
+    let excluded = |solver: &Solver, rows: &[usize]| {
+        let mut solutions = BTreeSet::new();
+        solver.solve_excluding(rows, |rows| {
+            rows.sort();
+            solutions.insert(rows.to_vec());
+            ControlFlow::<()>::Continue(())
+        });
+        solutions
+    };
+
+    assert_eq!(excluded(&variant, &[]), excluded(&solver, &[0, 3, 4]));
+    assert_eq!(
+        excluded(&variant, &[4, 5]),
+        excluded(&solver, &[0, 3, 4, 5])
+    );
+    assert_eq!(
+        variant.solve_with_fixed(&[0], |_| ControlFlow::<()>::Continue(())),
+        Err(FixedError::Disabled(0))
+    );
+
+    // Re-enable out of order, restoring the original row order
+    assert!(variant.enable_row(4));
+    assert!(variant.enable_row(0));
+    assert!(!variant.enable_row(0));
+    assert!(variant.enable_row(3));
+    variant.check_invariants();
+
+    assert_eq!(
+        alloc::format!("{}", variant.matrix),
+        alloc::format!("{}", solver.matrix)
+    );
+    assert_eq!(variant.solutions_sorted(), solver.solutions_sorted());
+    assert_eq!(
+        variant.collect_solutions(usize::MAX),
+        solver.collect_solutions(usize::MAX)
+    );
+}
+
+#[test]
+fn grouped() {
+    let solver = example();
+    let solutions = solver.solutions_sorted();
+
+    let groups = solver.solve_grouped(|rows| rows.len());
+    assert_eq!(groups.values().sum::<usize>(), solutions.len());
+    for (len, count) in &groups {
+        assert_eq!(
+            solutions.iter().filter(|rows| rows.len() == *len).count(),
+            *count
+        );
+    }
+
+    assert_eq!(
+        solver.solve_grouped(|_| ()),
+        BTreeMap::from([((), solutions.len())])
+    );
+}
+
+#[test]
+fn singleton() {
+    // Rows with one column have `head == tail`, linking to themselves
+    let mut solver = Solver::new(&[Bits(0b01), Bits(0b01), Bits(0b10)]);
+    assert_eq!(solver.solve_count(), 2);
+    assert_eq!(solver.row_columns(solver.rows[0]).count(), 1);
+
+    solver.add_row([1]);
+    assert_eq!(solver.solve_count(), 4);
+    assert_eq!(solver.row_columns(solver.rows[3]).count(), 1);
+}
+
+#[test]
+fn secondary() {
+    let rows = [Bits(0b0011), Bits(0b0110), Bits(0b0100), Bits(0b0001)];
+    assert_eq!(Solver::new(&rows).solve_count(), 2);
+
+    let solver = Solver::with_secondary(&rows, |col| col == 1);
+    let mut solutions = BTreeSet::new();
+    solver.solve(|rows| {
+        rows.sort();
+        solutions.insert(rows.to_vec());
+        core::ops::ControlFlow::<(), _>::Continue(())
+    });
+
+    // Column 1 may be left uncovered, but never covered twice
+    assert_eq!(
+        solutions,
+        BTreeSet::from([vec![0, 2], vec![1, 3], vec![2, 3]])
+    );
+}
+
+#[cfg(test)]
+struct Bits(u8);
+
+#[cfg(test)]
+impl Row for Bits {
+    fn iter(&self) -> impl Iterator<Item = u32> {
+        (0..8).filter(|bit| (self.0 >> bit) & 1 > 0)
+    }
+}
+
+/// Rows over four columns with solutions {0, 1}, {2, 3}, and {4, 5}.
+#[cfg(test)]
+fn example() -> Solver {
+    Solver::new(&example_rows())
+}
+
+#[cfg(test)]
+fn example_rows() -> [Bits; 6] {
+    [
+        Bits(0b0011),
+        Bits(0b1100),
+        Bits(0b0110),
+        Bits(0b1001),
+        Bits(0b0001),
+        Bits(0b1110),
+    ]
+}
+
+#[test]
+fn clone() {
+    let solver = example();
+
+    let clone = solver.clone();
+
+    let collect = |solver: &Solver| {
+        let mut solutions = BTreeSet::new();
+        solver.solve(|rows| {
+            rows.sort();
+            solutions.insert(rows.to_vec());
+            core::ops::ControlFlow::<(), _>::Continue(())
+        });
+        solutions
+    };
+
+    let expected = collect(&solver);
+    assert_eq!(expected.len(), 3);
+    assert_eq!(collect(&clone), expected);
+}
+
+#[test]
+fn wide() {
+    struct Row(core::ops::RangeInclusive<u32>);
+
+    impl crate::solve::Row for Row {
+        fn iter(&self) -> impl Iterator<Item = u32> {
+            self.0.clone()
+        }
+    }
+
+    // One more column than fits in a `u16`
+    let max = u16::MAX as u32;
+    let solver = Solver::new(&[Row(0..=max), Row(0..=0), Row(1..=max)]);
+    assert_eq!(solver.solve_count(), 2);
+
+    // Sparse IDs needn't be small, only few
+    let solver = Solver::new(&[Row(u32::MAX..=u32::MAX), Row(1 << 20..=1 << 20)]);
+    assert_eq!(solver.solve_count(), 1);
+    assert_eq!(solver.sparse_column(1), u32::MAX);
+    assert_eq!(solver.dense_column(1 << 20), Some(0));
+}
+
+#[test]
+fn fixed() {
+    let solver = example();
+
+    let collect = |fixed: &[usize]| {
+        let mut solutions = BTreeSet::new();
+        solver
+            .solve_with_fixed(fixed, |rows| {
+                rows.sort();
+                solutions.insert(rows.to_vec());
+                core::ops::ControlFlow::<(), _>::Continue(())
+            })
+            .map(|_| solutions)
+    };
+
+    assert_eq!(collect(&[1]), Ok(BTreeSet::from([vec![0, 1]])));
+    assert_eq!(collect(&[4]), Ok(BTreeSet::from([vec![4, 5]])));
+    assert_eq!(collect(&[0, 1]), Ok(BTreeSet::from([vec![0, 1]])));
+    assert_eq!(collect(&[0, 2]), Err(FixedError::Conflict(0, 2)));
+    assert_eq!(collect(&[6]), Err(FixedError::OutOfBounds(6)));
+
+    // Matrix is restored after each call
+    assert_eq!(solver.solve_count(), 3);
+    assert_eq!(
+        solver.solve(|rows| core::ops::ControlFlow::Break(rows.len())),
+        Some(2)
+    );
+    assert_eq!(solver.solve_count(), 3);
+}
+
+#[test]
+fn excluding() {
+    let solver = Solver::new(&[
+        Bits(0b0011),
+        Bits(0b1100),
+        Bits(0b0110),
+        Bits(0b1001),
+        Bits(0b0001),
+        Bits(0b1110),
+        Bits(0b10000),
+    ]);
+
+    let count = |excluded: &[usize]| {
+        let mut count = 0;
+        solver.solve_excluding(excluded, |_| {
+            count += 1;
+            core::ops::ControlFlow::<(), _>::Continue(())
+        });
+        count
+    };
+
+    assert_eq!(count(&[]), 3);
+    assert_eq!(count(&[0]), 2);
+    assert_eq!(count(&[0, 2, 0]), 1);
+    assert_eq!(count(&[0, 2, 4]), 0);
+
+    // Row 6 is the only row covering its column
+    assert_eq!(count(&[6]), 0);
+
+    // Matrix is restored after each call
+    assert_eq!(solver.solve_count(), 3);
+}
+
+#[test]
+#[should_panic(expected = "index out of bounds")]
+fn excluding_out_of_bounds() {
+    example().solve_excluding(&[6], |_| ControlFlow::<()>::Continue(()));
 }
@@ -0,0 +1,195 @@
+//! Explicit-stack search that can be stepped, checkpointed, and resumed.
+
+use alloc::vec::Vec;
+
+use crate::heuristic::Heuristic as _;
+use crate::matrix;
+use crate::solve::Solver;
+
+/// Algorithm X with the recursion replaced by an explicit stack of choices,
+/// so the search can be advanced one step at a time.
+///
+/// The solver's matrix is mutated as the search progresses. Use
+/// [`Search::into_solver`] to restore it.
+pub struct Search {
+    solver: Solver,
+    // Covered column and selected node at each level
+    stack: Vec<(matrix::Col, matrix::Index)>,
+    phase: Phase,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StepResult {
+    /// Selected a row and descended one level.
+    Select(usize),
+    /// Exhausted a column or reached a dead end, and returned to the
+    /// previous level.
+    Backtrack,
+    /// Found a solution, given as row indices.
+    Solution(Vec<usize>),
+    /// Search is complete.
+    Done,
+}
+
+/// Snapshot of a search's progress, which can be used to rebuild
+/// the search from a fresh solver via [`Search::restore`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchState {
+    /// Row selected at each level of the search.
+    pub rows: Vec<usize>,
+    pub phase: Phase,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Phase {
+    /// Next step chooses a column at a new level.
+    Descend,
+    /// Next step advances to the next row at the current level.
+    Backtrack,
+    Done,
+}
+
+impl Search {
+    /// Start a search over `solver` from the empty selection.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any column has an upper bound above one.
+    pub fn new(solver: Solver) -> Self {
+        solver.assert_at_most_once("Search");
+        Self {
+            solver,
+            stack: Vec::new(),
+            phase: Phase::Descend,
+        }
+    }
+
+    /// Rebuild a search from a snapshot by replaying its selections.
+    ///
+    /// Returns `None` if `state` does not correspond to a search over `solver`.
+    pub fn restore(solver: Solver, state: &SearchState) -> Option<Self> {
+        let mut search = Self::new(solver);
+
+        for row in &state.rows {
+            let Some(col) = search.choose() else {
+                return search.abort();
+            };
+
+            search.solver.cover(col);
+
+            let Some(index) = search
+                .solver
+                .matrix
+                .walk_down(col.into())
+                .find(|index| usize::from(search.solver.matrix[*index].row) == *row)
+            else {
+                search.solver.uncover(col);
+                return search.abort();
+            };
+
+            search.solver.select(index);
+            search.stack.push((col, index));
+        }
+
+        search.phase = state.phase;
+        Some(search)
+    }
+
+    pub fn save(&self) -> SearchState {
+        SearchState {
+            rows: self.rows(),
+            phase: self.phase,
+        }
+    }
+
+    /// Perform one cover, branch, or backtrack.
+    pub fn step(&mut self) -> StepResult {
+        match self.phase {
+            Phase::Done => StepResult::Done,
+            Phase::Descend => {
+                if self.is_complete() {
+                    self.phase = Phase::Backtrack;
+                    return StepResult::Solution(self.rows());
+                }
+
+                // Heuristic pruned this branch
+                let Some(col) = self.choose() else {
+                    self.phase = Phase::Backtrack;
+                    return StepResult::Backtrack;
+                };
+
+                self.solver.cover(col);
+                self.advance(col, col.into())
+            }
+            Phase::Backtrack => {
+                let Some((col, index)) = self.stack.pop() else {
+                    self.phase = Phase::Done;
+                    return StepResult::Done;
+                };
+
+                self.solver.deselect(index);
+                self.advance(col, index)
+            }
+        }
+    }
+
+    /// Unwind the search and return the solver with its matrix restored.
+    pub fn into_solver(mut self) -> Solver {
+        self.unwind();
+        self.solver
+    }
+
+    // Select the row after `index` in the covered column `col`,
+    // or uncover `col` and backtrack if there is none.
+    fn advance(&mut self, col: matrix::Col, index: matrix::Index) -> StepResult {
+        let next = self.solver.matrix.down(index);
+
+        if next == col.into() {
+            self.solver.uncover(col);
+            self.phase = Phase::Backtrack;
+            return StepResult::Backtrack;
+        }
+
+        self.solver.select(next);
+        self.stack.push((col, next));
+        self.phase = Phase::Descend;
+        StepResult::Select(usize::from(self.solver.matrix[next].row))
+    }
+
+    fn is_complete(&self) -> bool {
+        let matrix = &self.solver.matrix;
+        matrix.walk_right(matrix::Index::GLOBAL).next().is_none()
+    }
+
+    fn choose(&self) -> Option<matrix::Col> {
+        let matrix = &self.solver.matrix;
+        self.solver.heuristic().choose(
+            matrix
+                .walk_right(matrix::Index::GLOBAL)
+                .map(|index| matrix.index_to_column(index))
+                .map(|col| (col, matrix.size(col))),
+        )
+    }
+
+    fn rows(&self) -> Vec<usize> {
+        self.stack
+            .iter()
+            .map(|(_, index)| usize::from(self.solver.matrix[*index].row))
+            .collect()
+    }
+
+    fn unwind(&mut self) {
+        while let Some((col, index)) = self.stack.pop() {
+            self.solver.deselect(index);
+            self.solver.uncover(col);
+        }
+        self.phase = Phase::Done;
+    }
+
+    fn abort(mut self) -> Option<Self> {
+        self.unwind();
+        None
+    }
+}
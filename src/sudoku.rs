@@ -0,0 +1,204 @@
+//! Exact cover encoding of 9x9 Sudoku.
+//!
+//! Each row of the matrix places one digit in one cell, and covers four
+//! constraints: the cell is filled, and the digit appears in the cell's
+//! row, column, and box.
+
+use core::fmt::Display;
+use core::ops::ControlFlow;
+use core::str::FromStr;
+
+use alloc::vec::Vec;
+
+use crate::solve;
+use crate::solve::Solver;
+
+pub type Grid = [[u8; 9]; 9];
+
+/// Puzzle with givens as digits `1..=9` and blanks as `0`.
+#[derive(Clone)]
+pub struct Sudoku {
+    grid: Grid,
+    solver: Solver,
+}
+
+impl Sudoku {
+    /// Fails if any cell holds a digit above 9.
+    pub fn new(grid: Grid) -> Result<Self, InvalidDigit> {
+        for (i, row) in grid.iter().enumerate() {
+            for (j, digit) in row.iter().enumerate() {
+                if *digit > 9 {
+                    return Err(InvalidDigit {
+                        i,
+                        j,
+                        digit: *digit,
+                    });
+                }
+            }
+        }
+
+        let placements = (0..9)
+            .flat_map(|i| {
+                (0..9).flat_map(move |j| (0..9).map(move |digit| Placement { i, j, digit }))
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Self {
+            grid,
+            solver: Solver::new(&placements),
+        })
+    }
+
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Return the first solution, or `None` if the givens conflict
+    /// or admit no solution.
+    pub fn solve(&self) -> Option<Grid> {
+        self.solver
+            .solve_with_fixed(&self.givens(), |rows| {
+                let mut grid = [[0; 9]; 9];
+                for placement in rows.iter().map(|row| Placement::from_row(*row)) {
+                    grid[placement.i as usize][placement.j as usize] = placement.digit as u8 + 1;
+                }
+                ControlFlow::Break(grid)
+            })
+            .ok()
+            .flatten()
+    }
+
+    pub fn count_solutions(&self) -> usize {
+        let mut count = 0;
+        let _ = self.solver.solve_with_fixed(&self.givens(), |_| {
+            count += 1;
+            ControlFlow::<(), ()>::Continue(())
+        });
+        count
+    }
+
+    fn givens(&self) -> Vec<usize> {
+        (0..9)
+            .flat_map(|i| (0..9).map(move |j| (i, j)))
+            .filter_map(|(i, j)| match self.grid[i as usize][j as usize] {
+                0 => None,
+                digit => Some(
+                    Placement {
+                        i,
+                        j,
+                        digit: digit as u16 - 1,
+                    }
+                    .to_row(),
+                ),
+            })
+            .collect()
+    }
+}
+
+/// Parses 81 cells in row-major order, where `1` through `9` are givens
+/// and `0` or `.` are blanks. Whitespace is ignored.
+impl FromStr for Sudoku {
+    type Err = ParseSudokuError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut grid = [[0; 9]; 9];
+        let mut len = 0;
+
+        for char in s.chars().filter(|char| !char.is_whitespace()) {
+            let digit = match char {
+                '.' | '0' => 0,
+                '1'..='9' => char as u8 - b'0',
+                char => return Err(ParseSudokuError::InvalidCell(char)),
+            };
+
+            if let Some(cell) = grid.get_mut(len / 9).map(|row| &mut row[len % 9]) {
+                *cell = digit;
+            }
+
+            len += 1;
+        }
+
+        match len {
+            // Parsed digits are always in range
+            81 => Ok(Sudoku::new(grid).expect("Parsed digit out of range")),
+            len => Err(ParseSudokuError::Length(len)),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParseSudokuError {
+    /// Cell is neither a digit nor `.`.
+    InvalidCell(char),
+    /// Number of cells is not 81.
+    Length(usize),
+}
+
+impl Display for ParseSudokuError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseSudokuError::InvalidCell(char) => write!(f, "Invalid cell {:?}", char),
+            ParseSudokuError::Length(len) => write!(f, "Expected 81 cells, found {}", len),
+        }
+    }
+}
+
+impl core::error::Error for ParseSudokuError {}
+
+/// Cell `(i, j)` of a [`Grid`] holds `digit`, which is neither a given
+/// `1..=9` nor a blank `0`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InvalidDigit {
+    pub i: usize,
+    pub j: usize,
+    pub digit: u8,
+}
+
+impl Display for InvalidDigit {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Invalid digit {} at ({}, {})",
+            self.digit, self.i, self.j
+        )
+    }
+}
+
+impl core::error::Error for InvalidDigit {}
+
+// Digit is zero-indexed.
+#[derive(Copy, Clone, Debug)]
+struct Placement {
+    i: u16,
+    j: u16,
+    digit: u16,
+}
+
+impl Placement {
+    fn from_row(row: usize) -> Self {
+        let row = row as u16;
+        Self {
+            i: row / 81,
+            j: row / 9 % 9,
+            digit: row % 9,
+        }
+    }
+
+    fn to_row(self) -> usize {
+        (self.i * 81 + self.j * 9 + self.digit) as usize
+    }
+}
+
+impl solve::Row for Placement {
+    fn iter(&self) -> impl Iterator<Item = u32> {
+        let b = self.i / 3 * 3 + self.j / 3;
+        [
+            self.i * 9 + self.j,
+            81 + self.i * 9 + self.digit,
+            162 + self.j * 9 + self.digit,
+            243 + b * 9 + self.digit,
+        ]
+        .into_iter()
+        .map(u32::from)
+    }
+}
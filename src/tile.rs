@@ -1,5 +1,18 @@
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
 use core::cmp::Ordering;
+use core::fmt::Display;
+use core::ops::ControlFlow;
 
+use crate::solve;
+use crate::solve::Solver;
+
+/// A collection of tiles transformed together as one shape.
+///
+/// The empty set has bounding box `(0, 0)`, and every transformation
+/// returns it unchanged.
 // Invariant: `self.0` is sorted.
 #[derive(Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Set<const LEN: usize>(Vec<Tile<LEN>>);
@@ -9,6 +22,15 @@ impl<const LEN: usize> Set<LEN> {
         Self(Vec::new())
     }
 
+    /// Create an empty set with room for `capacity` tiles.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(Vec::with_capacity(capacity))
+    }
+
+    /// Insert a single tile in O(n log n), re-sorting the whole set.
+    ///
+    /// Building a set of n tiles this way costs O(n² log n), so prefer
+    /// [`Extend::extend`] or [`FromIterator`], which sort once at the end.
     pub fn push(&mut self, tile: Tile<LEN>) {
         self.0.push(tile);
         self.0.sort();
@@ -38,14 +60,118 @@ impl<const LEN: usize> Set<LEN> {
         self.transform_clamp(SPoint::rotate_270)
     }
 
-    pub fn canonicalize(&self) -> Self {
-        [self.clone(), self.reflect_x(), self.reflect_y()]
+    /// Reflect across the main diagonal, swapping rows and columns.
+    pub fn transpose(&self) -> Self {
+        self.transform_clamp(SPoint::transpose)
+    }
+
+    /// Reflect across the anti-diagonal.
+    pub fn anti_transpose(&self) -> Self {
+        self.transform_clamp(SPoint::anti_transpose)
+    }
+
+    /// Apply `f` to every tile, re-sorting the results.
+    ///
+    /// Unlike the fixed symmetries above, tiles aren't clamped back
+    /// against the origin together, so `f` can translate or relabel freely.
+    pub fn map<F: FnMut(&Tile<LEN>) -> Tile<LEN>>(&self, f: F) -> Self {
+        self.0.iter().map(f).collect()
+    }
+
+    /// Return the distinct rotations of this set, excluding reflections.
+    pub fn rotations(&self) -> impl Iterator<Item = Self> {
+        let mut rotations = Vec::with_capacity(4);
+
+        for set in [
+            self.normalized(),
+            self.rotate_90(),
+            self.rotate_180(),
+            self.rotate_270(),
+        ] {
+            if !rotations.contains(&set) {
+                rotations.push(set);
+            }
+        }
+
+        rotations.into_iter()
+    }
+
+    /// Return the distinct rotations and reflections of this set, with
+    /// duplicates from symmetry removed.
+    pub fn distinct_transformations(&self) -> BTreeSet<Self> {
+        // Rotating the set and its mirror image reaches all eight
+        [self.normalized(), self.reflect_x()]
             .into_iter()
-            .flat_map(|set| [set.rotate_90(), set.rotate_180(), set.rotate_270(), set])
-            .min()
+            .flat_map(|set| {
+                let rotations = [set.rotate_90(), set.rotate_180(), set.rotate_270()];
+                core::iter::once(set).chain(rotations)
+            })
+            .collect()
+    }
+
+    /// Return tiles in either set, merging in O(n + m).
+    pub fn union(&self, other: &Self) -> Self {
+        self.merge(other, true, true, true)
+    }
+
+    /// Return tiles in both sets, merging in O(n + m).
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.merge(other, false, true, false)
+    }
+
+    /// Return tiles in `self` but not `other`, merging in O(n + m).
+    pub fn difference(&self, other: &Self) -> Self {
+        self.merge(other, true, false, false)
+    }
+
+    // Walk both sorted vectors in lockstep, keeping tiles only on the left,
+    // in both, or only on the right. Duplicate tiles pair off one-to-one.
+    fn merge(&self, other: &Self, left: bool, both: bool, right: bool) -> Self {
+        let mut tiles = Vec::with_capacity(self.0.len() + other.0.len());
+        let (mut i, mut j) = (0, 0);
+
+        while let (Some(a), Some(b)) = (self.0.get(i), other.0.get(j)) {
+            match a.cmp(b) {
+                Ordering::Less => {
+                    tiles.extend(left.then_some(*a));
+                    i += 1;
+                }
+                Ordering::Equal => {
+                    tiles.extend(both.then_some(*a));
+                    i += 1;
+                    j += 1;
+                }
+                Ordering::Greater => {
+                    tiles.extend(right.then_some(*b));
+                    j += 1;
+                }
+            }
+        }
+
+        if left {
+            tiles.extend_from_slice(&self.0[i..]);
+        }
+
+        if right {
+            tiles.extend_from_slice(&other.0[j..]);
+        }
+
+        Self(tiles)
+    }
+
+    /// Return the least of the eight rotations and reflections of this set,
+    /// so sets equal up to symmetry canonicalize to the same value.
+    pub fn canonicalize(&self) -> Self {
+        self.distinct_transformations()
+            .pop_first()
             .unwrap_or_default()
     }
 
+    // Translate tiles together so the set touches both the top and left edges.
+    fn normalized(&self) -> Self {
+        self.transform_clamp(|point| *point)
+    }
+
     fn transform_clamp<F: FnMut(&SPoint) -> SPoint>(&self, mut apply: F) -> Self {
         Self::clamp(
             &self
@@ -57,46 +183,291 @@ impl<const LEN: usize> Set<LEN> {
     }
 
     fn clamp(tiles: &[[SPoint; LEN]]) -> Self {
-        let min_i = tiles
-            .iter()
-            .flatten()
-            .map(|point| point.i)
-            .min()
-            .unwrap_or(0);
+        let mut tiles = clamp_all(tiles);
+        tiles.sort();
+        Self(tiles)
+    }
 
-        let min_j = tiles
-            .iter()
-            .flatten()
-            .map(|point| point.j)
+    /// Return the `(height, width)` spanned from the origin to the furthest
+    /// point of any tile, which is the bounding box of the set once clamped.
+    pub fn bounding_box(&self) -> (u16, u16) {
+        self.iter()
+            .map(Tile::dimensions)
+            .fold((0, 0), |(rows, cols), (i, j)| (rows.max(i), cols.max(j)))
+    }
+
+    /// Return whether the tiles cover every point of the `rows` by `cols`
+    /// grid satisfying `filter` exactly once, and no other point.
+    pub fn tiles_exactly<F: FnMut(Point) -> bool>(
+        &self,
+        rows: u16,
+        cols: u16,
+        mut filter: F,
+    ) -> bool {
+        let mut covered = vec![false; rows as usize * cols as usize];
+
+        for point in self.iter().flat_map(|tile| tile.as_ref()) {
+            if point.i >= rows || point.j >= cols || !filter(*point) {
+                return false;
+            }
+
+            let cell = &mut covered[point.i as usize * cols as usize + point.j as usize];
+            if core::mem::replace(cell, true) {
+                return false;
+            }
+        }
+
+        (0..rows)
+            .flat_map(|i| (0..cols).map(move |j| Point { i, j }))
+            .zip(covered)
+            .all(|(point, covered)| covered || !filter(point))
+    }
+
+    /// Render tiles onto a `rows` by `cols` grid, labeling each tile's
+    /// cells with a distinct character and empty cells with `.`.
+    pub fn render(&self, rows: u16, cols: u16) -> String {
+        const LABELS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+        let mut grid = vec![vec!['.'; cols as usize]; rows as usize];
+
+        for (index, tile) in self.iter().enumerate() {
+            for point in tile.as_ref() {
+                if let Some(cell) = grid
+                    .get_mut(point.i as usize)
+                    .and_then(|row| row.get_mut(point.j as usize))
+                {
+                    *cell = LABELS[index % LABELS.len()] as char;
+                }
+            }
+        }
+
+        grid.into_iter()
+            .flat_map(|row| row.into_iter().chain(core::iter::once('\n')))
+            .collect()
+    }
+}
+
+#[cfg(feature = "svg")]
+impl<const LEN: usize> Set<LEN> {
+    /// Render tiles as an SVG image sized to the set's bounding box, with
+    /// one `cell_size` square `<rect>` per point, colored by tile.
+    pub fn to_svg(&self, cell_size: u32) -> String {
+        use core::fmt::Write as _;
+
+        let (rows, cols) = self.bounding_box();
+        let mut svg = String::new();
+
+        // Writing to a `String` never fails
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}">"#,
+            cols as u32 * cell_size,
+            rows as u32 * cell_size,
+        );
+
+        for (index, tile) in self.iter().enumerate() {
+            // Spread hues evenly around the color wheel
+            let hue = index * 360 / self.0.len();
+            for point in tile.as_ref() {
+                let _ = writeln!(
+                    svg,
+                    r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="hsl({}, 70%, 60%)"/>"#,
+                    point.j as u32 * cell_size,
+                    point.i as u32 * cell_size,
+                    cell_size,
+                    cell_size,
+                    hue,
+                );
+            }
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+impl<const LEN: usize> FromIterator<Tile<LEN>> for Set<LEN> {
+    fn from_iter<T: IntoIterator<Item = Tile<LEN>>>(iter: T) -> Self {
+        let mut tiles = Vec::from_iter(iter);
+        tiles.sort();
+        Self(tiles)
+    }
+}
+
+impl<const LEN: usize> Extend<Tile<LEN>> for Set<LEN> {
+    /// Insert every tile, sorting once at the end.
+    fn extend<T: IntoIterator<Item = Tile<LEN>>>(&mut self, iter: T) {
+        self.0.extend(iter);
+        self.0.sort();
+    }
+}
+
+// Translate tiles together so their union touches both axes, preserving order.
+fn clamp_all<const LEN: usize>(tiles: &[[SPoint; LEN]]) -> Vec<Tile<LEN>> {
+    let min_i = tiles
+        .iter()
+        .flatten()
+        .map(|point| point.i)
+        .min()
+        .unwrap_or(0);
+
+    let min_j = tiles
+        .iter()
+        .flatten()
+        .map(|point| point.j)
+        .min()
+        .unwrap_or(0);
+
+    tiles
+        .iter()
+        .map(|tile| {
+            core::array::from_fn(|index| Point::from(tile[index].translate(-min_i, -min_j)))
+        })
+        .map(Tile::new)
+        .collect()
+}
+
+/// A [`Set`] whose tiles each carry an identifier, such as the label of
+/// the piece they were placed from.
+///
+/// Transformations move each tile along with its identifier.
+// Invariant: `self.0` is sorted.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IdSet<T, const LEN: usize>(Vec<(T, Tile<LEN>)>);
+
+impl<T: Ord + Clone, const LEN: usize> IdSet<T, LEN> {
+    pub const fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn push(&mut self, id: T, tile: Tile<LEN>) {
+        self.0.push((id, tile));
+        self.0.sort();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(T, Tile<LEN>)> {
+        self.0.iter()
+    }
+
+    /// Discard identifiers, keeping only the tiles.
+    pub fn to_set(&self) -> Set<LEN> {
+        self.0.iter().map(|(_, tile)| *tile).collect()
+    }
+
+    pub fn reflect_x(&self) -> Self {
+        self.transform_clamp(SPoint::reflect_x)
+    }
+
+    pub fn reflect_y(&self) -> Self {
+        self.transform_clamp(SPoint::reflect_y)
+    }
+
+    pub fn rotate_90(&self) -> Self {
+        self.transform_clamp(SPoint::rotate_90)
+    }
+
+    pub fn rotate_180(&self) -> Self {
+        self.transform_clamp(SPoint::rotate_180)
+    }
+
+    pub fn rotate_270(&self) -> Self {
+        self.transform_clamp(SPoint::rotate_270)
+    }
+
+    /// Reflect across the main diagonal, swapping rows and columns.
+    pub fn transpose(&self) -> Self {
+        self.transform_clamp(SPoint::transpose)
+    }
+
+    /// Reflect across the anti-diagonal.
+    pub fn anti_transpose(&self) -> Self {
+        self.transform_clamp(SPoint::anti_transpose)
+    }
+
+    /// Return the least of the eight rotations and reflections of this set,
+    /// comparing identifiers before tiles.
+    pub fn canonicalize(&self) -> Self {
+        [self.transform_clamp(|point| *point), self.reflect_x()]
+            .into_iter()
+            .flat_map(|set| [set.rotate_90(), set.rotate_180(), set.rotate_270(), set])
             .min()
-            .unwrap_or(0);
+            .unwrap_or_default()
+    }
+
+    /// Return the `(height, width)` spanned from the origin to the furthest
+    /// point of any tile.
+    pub fn bounding_box(&self) -> (u16, u16) {
+        self.to_set().bounding_box()
+    }
+
+    /// Render tiles onto a `rows` by `cols` grid, labeling each tile's
+    /// cells with its identifier and empty cells with `.`.
+    pub fn render(&self, rows: u16, cols: u16) -> String
+    where
+        T: Into<char>,
+    {
+        let mut grid = vec![vec!['.'; cols as usize]; rows as usize];
+
+        for (id, tile) in self.iter() {
+            for point in tile.as_ref() {
+                if let Some(cell) = grid
+                    .get_mut(point.i as usize)
+                    .and_then(|row| row.get_mut(point.j as usize))
+                {
+                    *cell = id.clone().into();
+                }
+            }
+        }
+
+        grid.into_iter()
+            .flat_map(|row| row.into_iter().chain(core::iter::once('\n')))
+            .collect()
+    }
 
-        let mut tiles = tiles
+    fn transform_clamp<F: FnMut(&SPoint) -> SPoint>(&self, mut apply: F) -> Self {
+        let tiles = self
+            .0
             .iter()
-            .map(|tile| {
-                core::array::from_fn(|index| Point::from(tile[index].translate(-min_i, -min_j)))
-            })
-            .map(Tile::new)
+            .map(|(_, tile)| tile.transform(&mut apply))
             .collect::<Vec<_>>();
 
-        tiles.sort();
-        Self(tiles)
+        self.0
+            .iter()
+            .map(|(id, _)| id.clone())
+            .zip(clamp_all(&tiles))
+            .collect()
     }
 }
 
-impl<const LEN: usize> FromIterator<Tile<LEN>> for Set<LEN> {
-    fn from_iter<T: IntoIterator<Item = Tile<LEN>>>(iter: T) -> Self {
+// Manual impl to avoid requiring `T: Default`
+impl<T, const LEN: usize> Default for IdSet<T, LEN> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T: Ord, const LEN: usize> FromIterator<(T, Tile<LEN>)> for IdSet<T, LEN> {
+    fn from_iter<I: IntoIterator<Item = (T, Tile<LEN>)>>(iter: I) -> Self {
         let mut tiles = Vec::from_iter(iter);
         tiles.sort();
         Self(tiles)
     }
 }
 
+/// A polyomino of `LEN` cells.
+///
+/// `Tile<0>` is the empty tile. It is well-defined rather than forbidden:
+/// its dimensions are `(0, 0)`, it renders as an empty grid, it is trivially
+/// connected, and every transformation returns it unchanged.
 // Invariant: `self.0` is sorted.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Tile<const LEN: usize>([Point; LEN]);
 
 impl<const LEN: usize> Tile<LEN> {
+    /// Construct a tile from `points`, which are sorted but not translated.
+    ///
+    /// Tiles offset from the origin compare unequal to their clamped
+    /// counterparts; see [`Tile::normalized`] and [`Tile::same_shape`].
     pub const fn new(mut points: [Point; LEN]) -> Self {
         // Manual bubble sort to preserve `const` compatibility :(
         'outer: loop {
@@ -105,8 +476,8 @@ impl<const LEN: usize> Tile<LEN> {
 
             while i + 1 < points.len() {
                 match points[i].cmp(&points[i + 1]) {
-                    Ordering::Less => i += 1,
-                    Ordering::Equal | Ordering::Greater => {
+                    Ordering::Less | Ordering::Equal => i += 1,
+                    Ordering::Greater => {
                         swap = points[i];
                         points[i] = points[i + 1];
                         points[i + 1] = swap;
@@ -121,12 +492,183 @@ impl<const LEN: usize> Tile<LEN> {
         Self(points)
     }
 
+    /// Construct a tile from `points` like [`Tile::new`], but fail if any
+    /// point appears more than once.
+    ///
+    /// Connectivity is not checked; see [`Tile::is_connected`].
+    pub fn from_points(points: [Point; LEN]) -> Result<Self, TileError> {
+        let tile = Self::new(points);
+        match tile.0.windows(2).any(|pair| pair[0] == pair[1]) {
+            true => Err(TileError::Duplicate),
+            false => Ok(tile),
+        }
+    }
+
+    /// Parse a grid of `X` (filled) and `.` (empty) cells with one row per
+    /// line, assigning coordinates like the [`tile!`](crate::tile!) macro.
+    ///
+    /// Whitespace within and around rows is ignored.
+    pub fn from_grid(grid: &str) -> Result<Self, ParseTileError> {
+        let mut points = Vec::with_capacity(LEN);
+
+        for (i, line) in grid.trim().lines().enumerate() {
+            for (j, cell) in line
+                .chars()
+                .filter(|char| !char.is_whitespace())
+                .enumerate()
+            {
+                match cell {
+                    '.' => continue,
+                    'X' => (),
+                    char => return Err(ParseTileError::InvalidCell(char)),
+                }
+
+                let (Ok(i), Ok(j)) = (u16::try_from(i), u16::try_from(j)) else {
+                    return Err(ParseTileError::Overflow);
+                };
+
+                points.push(Point { i, j });
+            }
+        }
+
+        let actual = points.len();
+        <[Point; LEN]>::try_from(points)
+            .map(Tile::new)
+            .map_err(|_| ParseTileError::Count {
+                expected: LEN,
+                actual,
+            })
+    }
+
+    /// Translate the tile so that it touches both the top and left edges.
+    pub fn normalized(&self) -> Self {
+        self.transform_clamp(|point| *point)
+    }
+
+    /// Check whether the tiles are equal up to translation.
+    pub fn same_shape(&self, other: &Self) -> bool {
+        self.normalized() == other.normalized()
+    }
+
+    /// Return the number of cells in the tile, which is always `LEN`.
+    pub const fn area(&self) -> usize {
+        LEN
+    }
+
+    /// Return the `(height, width)` spanned from the origin to the tile's
+    /// furthest point, which is its bounding box once clamped.
+    pub fn dimensions(&self) -> (u16, u16) {
+        let rows = self.0.iter().map(|point| point.i + 1).max().unwrap_or(0);
+        let cols = self.0.iter().map(|point| point.j + 1).max().unwrap_or(0);
+        (rows, cols)
+    }
+
+    /// Yield the tile's cells in sorted order.
+    pub fn cells(&self) -> impl Iterator<Item = Point> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.0.binary_search(&point).is_ok()
+    }
+
+    /// Check whether the cells form a single orthogonally connected polyomino.
+    pub fn is_connected(&self) -> bool {
+        self.is_connected_by(|di, dj| di + dj == 1)
+    }
+
+    /// Check whether the cells form a single group when diagonal
+    /// neighbors are also considered adjacent.
+    pub fn is_connected_diagonal(&self) -> bool {
+        self.is_connected_by(|di, dj| di.max(dj) == 1)
+    }
+
+    fn is_connected_by<F: Fn(u16, u16) -> bool>(&self, adjacent: F) -> bool {
+        let mut seen = [false; LEN];
+        let mut stack = Vec::with_capacity(LEN);
+
+        if LEN > 0 {
+            seen[0] = true;
+            stack.push(0);
+        }
+
+        while let Some(a) = stack.pop() {
+            let a = self.0[a];
+            for (b, (point, seen)) in self.0.iter().zip(&mut seen).enumerate() {
+                if !*seen && adjacent(a.i.abs_diff(point.i), a.j.abs_diff(point.j)) {
+                    *seen = true;
+                    stack.push(b);
+                }
+            }
+        }
+
+        seen.iter().all(|seen| *seen)
+    }
+
+    /// Render as a grid of `X` and `.` cells spanning from the origin to
+    /// the tile's furthest point, the inverse of [`Tile::from_grid`].
+    pub fn to_grid(&self) -> String {
+        let (rows, cols) = self.dimensions();
+
+        (0..rows)
+            .flat_map(|i| {
+                (0..cols)
+                    .map(move |j| match self.contains(Point { i, j }) {
+                        true => 'X',
+                        false => '.',
+                    })
+                    .chain(core::iter::once('\n'))
+            })
+            .collect()
+    }
+
+    /// Yield the distinct rotations of this tile, i.e. its one-sided orientations.
+    ///
+    /// Unlike [`Tile::transformations`], this excludes reflections, and
+    /// duplicates from rotational symmetry are removed.
+    pub fn rotations(&self) -> impl Iterator<Item = Self> {
+        let mut rotations = Vec::with_capacity(4);
+
+        for tile in [
+            self.normalized(),
+            self.rotate_90(),
+            self.rotate_180(),
+            self.rotate_270(),
+        ] {
+            if !rotations.contains(&tile) {
+                rotations.push(tile);
+            }
+        }
+
+        rotations.into_iter()
+    }
+
+    /// Yield the distinct rotations and reflections of this tile, i.e. its
+    /// free orientations, in sorted order.
+    ///
+    /// The count reflects the tile's symmetry group: among the pentominoes,
+    /// 1 for the X, 2 for the straight O, 4 for the T, U, V, W, and Z, and 8
+    /// for the rest. See [`Tile::transformations_raw`] to keep duplicates.
     pub fn transformations(&self) -> impl Iterator<Item = Self> {
-        [*self, self.reflect_x(), self.reflect_y()]
+        self.distinct_transformations().into_iter()
+    }
+
+    /// Yield all eight rotations and reflections of this tile, including
+    /// duplicates from symmetry, each translated back to the origin.
+    pub fn transformations_raw(&self) -> impl Iterator<Item = Self> {
+        // Rotating one reflection reaches the other three. Normalize the
+        // untransformed tile too, or an offset tile would never match.
+        [self.normalized(), self.reflect_x()]
             .into_iter()
             .flat_map(|tile| [tile, tile.rotate_90(), tile.rotate_180(), tile.rotate_270()])
     }
 
+    /// Return the distinct rotations and reflections of this tile, as
+    /// yielded by [`Tile::transformations`].
+    pub fn distinct_transformations(&self) -> BTreeSet<Self> {
+        self.transformations_raw().collect()
+    }
+
     pub fn reflect_x(&self) -> Self {
         self.transform_clamp(SPoint::reflect_x)
     }
@@ -147,10 +689,105 @@ impl<const LEN: usize> Tile<LEN> {
         self.transform_clamp(SPoint::rotate_270)
     }
 
+    /// Reflect across the main diagonal, swapping rows and columns.
+    pub fn transpose(&self) -> Self {
+        self.transform_clamp(SPoint::transpose)
+    }
+
+    /// Reflect across the anti-diagonal.
+    pub fn anti_transpose(&self) -> Self {
+        self.transform_clamp(SPoint::anti_transpose)
+    }
+
+    /// Return the first of [`Transform::ALL`] mapping this tile onto
+    /// `other` up to translation, or `None` if they are different shapes.
+    pub fn is_symmetric_to(&self, other: &Self) -> Option<Transform> {
+        let other = other.normalized();
+        Transform::ALL
+            .into_iter()
+            .find(|transform| transform.apply(self) == other)
+    }
+
+    /// Equivalent to `*self = self.reflect_x()`, transforming the points
+    /// in place and re-sorting them. The same holds for the other
+    /// `_in_place` methods.
+    pub fn reflect_x_in_place(&mut self) {
+        self.transform_clamp_in_place(SPoint::reflect_x)
+    }
+
+    pub fn reflect_y_in_place(&mut self) {
+        self.transform_clamp_in_place(SPoint::reflect_y)
+    }
+
+    pub fn rotate_90_in_place(&mut self) {
+        self.transform_clamp_in_place(SPoint::rotate_90)
+    }
+
+    pub fn rotate_180_in_place(&mut self) {
+        self.transform_clamp_in_place(SPoint::rotate_180)
+    }
+
+    pub fn rotate_270_in_place(&mut self) {
+        self.transform_clamp_in_place(SPoint::rotate_270)
+    }
+
+    pub fn transpose_in_place(&mut self) {
+        self.transform_clamp_in_place(SPoint::transpose)
+    }
+
+    pub fn anti_transpose_in_place(&mut self) {
+        self.transform_clamp_in_place(SPoint::anti_transpose)
+    }
+
+    /// Apply the integer linear map `m` to each point, treating points as
+    /// column vectors `[i, j]`, then translate the result back to the origin.
+    ///
+    /// For example, `[[0, -1], [1, 0]]` is equivalent to [`Tile::rotate_90`],
+    /// and `[[1, 1], [0, 1]]` shears rows by their column.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `m` is singular, since distinct points could then collide,
+    /// or if the result does not fit in `u16` coordinates.
+    pub fn transform_linear(&self, m: [[i8; 2]; 2]) -> Self {
+        assert!(
+            m[0][0] as i32 * m[1][1] as i32 != m[0][1] as i32 * m[1][0] as i32,
+            "Singular transform {:?}",
+            m,
+        );
+        self.transform_clamp(|point| point.linear(m))
+    }
+
     fn transform_clamp<F: FnMut(&SPoint) -> SPoint>(&self, apply: F) -> Self {
         Self::clamp(self.transform(apply))
     }
 
+    // Apply `apply` twice per point, once to find the new origin and once
+    // to write the result, to avoid an intermediate array.
+    fn transform_clamp_in_place<F: Fn(&SPoint) -> SPoint>(&mut self, apply: F) {
+        let (min_i, min_j) = self
+            .0
+            .iter()
+            .map(|point| apply(&SPoint::from(*point)))
+            .fold((i32::MAX, i32::MAX), |(i, j), point| {
+                (i.min(point.i), j.min(point.j))
+            });
+
+        for point in &mut self.0 {
+            *point = Point::from(apply(&SPoint::from(*point)).translate(-min_i, -min_j));
+        }
+
+        self.0.sort_unstable();
+    }
+
+    pub(crate) fn translate(&self, di: u16, dj: u16) -> Option<Self> {
+        let mut points = self.0;
+        for point in &mut points {
+            *point = point.translate(di.into(), dj.into())?;
+        }
+        Some(Self(points))
+    }
+
     fn transform<F: FnMut(&SPoint) -> SPoint>(&self, mut apply: F) -> [SPoint; LEN] {
         core::array::from_fn(|index| apply(&SPoint::from(self.0[index])))
     }
@@ -165,13 +802,135 @@ impl<const LEN: usize> Tile<LEN> {
     }
 }
 
+/// One of the eight symmetries of the square, as applied by the
+/// corresponding [`Tile`] method.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Transform {
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    ReflectX,
+    ReflectY,
+    Transpose,
+    AntiTranspose,
+}
+
+impl Transform {
+    pub const ALL: [Self; 8] = [
+        Self::Identity,
+        Self::Rotate90,
+        Self::Rotate180,
+        Self::Rotate270,
+        Self::ReflectX,
+        Self::ReflectY,
+        Self::Transpose,
+        Self::AntiTranspose,
+    ];
+
+    /// Apply this symmetry to `tile`, translating the result back to the
+    /// origin. [`Transform::Identity`] is equivalent to [`Tile::normalized`].
+    pub fn apply<const LEN: usize>(&self, tile: &Tile<LEN>) -> Tile<LEN> {
+        match self {
+            Self::Identity => tile.normalized(),
+            Self::Rotate90 => tile.rotate_90(),
+            Self::Rotate180 => tile.rotate_180(),
+            Self::Rotate270 => tile.rotate_270(),
+            Self::ReflectX => tile.reflect_x(),
+            Self::ReflectY => tile.reflect_y(),
+            Self::Transpose => tile.transpose(),
+            Self::AntiTranspose => tile.anti_transpose(),
+        }
+    }
+}
+
+/// The distinct rotations and reflections of a tile, computed once.
+///
+/// Orientations are sorted, so they line up with
+/// [`Tile::transformations`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TileOrbit<const LEN: usize>(Vec<Tile<LEN>>);
+
+impl<const LEN: usize> TileOrbit<LEN> {
+    pub fn new(tile: &Tile<LEN>) -> Self {
+        Self(tile.transformations().collect())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Tile<LEN>> {
+        self.0.iter()
+    }
+
+    /// Return whether `tile` is one of the orientations in this orbit.
+    pub fn contains(&self, tile: &Tile<LEN>) -> bool {
+        self.0.binary_search(tile).is_ok()
+    }
+}
+
+impl<const LEN: usize> AsRef<[Tile<LEN>]> for TileOrbit<LEN> {
+    fn as_ref(&self) -> &[Tile<LEN>] {
+        &self.0
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParseTileError {
+    /// Cell is neither `X` nor `.`.
+    InvalidCell(char),
+    /// Number of `X` cells doesn't match the tile size.
+    Count { expected: usize, actual: usize },
+    /// Coordinate doesn't fit in a `Point`.
+    Overflow,
+}
+
+impl Display for ParseTileError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseTileError::InvalidCell(char) => write!(f, "Invalid cell {:?}", char),
+            ParseTileError::Count { expected, actual } => {
+                write!(f, "Expected {} filled cells, found {}", expected, actual)
+            }
+            ParseTileError::Overflow => write!(f, "Grid exceeds maximum dimensions"),
+        }
+    }
+}
+
+impl core::error::Error for ParseTileError {}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TileError {
+    /// Some point appears more than once.
+    Duplicate,
+}
+
+impl Display for TileError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TileError::Duplicate => write!(f, "Duplicate point in tile"),
+        }
+    }
+}
+
+impl core::error::Error for TileError {}
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
-    pub i: u8,
-    pub j: u8,
+    pub i: u16,
+    pub j: u16,
 }
 
 impl Point {
+    /// Offset by `di` rows and `dj` columns, or return `None` if either
+    /// coordinate would go negative or overflow.
+    pub fn translate(self, di: i32, dj: i32) -> Option<Self> {
+        let i = i32::from(self.i).checked_add(di)?;
+        let j = i32::from(self.j).checked_add(dj)?;
+        Some(Self {
+            i: u16::try_from(i).ok()?,
+            j: u16::try_from(j).ok()?,
+        })
+    }
+
     const fn cmp(&self, other: &Self) -> Ordering {
         if self.i > other.i {
             return Ordering::Greater;
@@ -189,54 +948,345 @@ impl Point {
     }
 }
 
+/// The twelve free pentominoes, in Conway's labeling order (`'O'` through `'Z'`).
+pub const PENTOMINOES: [Tile<5>; 12] = [
+    // O
+    crate::tile! {
+        X X X X X
+    },
+    // P
+    crate::tile! {
+        X X;
+        X X;
+        X
+    },
+    // Q
+    crate::tile! {
+        X X X X;
+        . . . X
+    },
+    // R
+    crate::tile! {
+        . X X;
+        X X;
+        . X
+    },
+    // S
+    crate::tile! {
+        X;
+        X X;
+        . X;
+        . X
+    },
+    // T
+    crate::tile! {
+        X X X;
+        . X;
+        . X
+    },
+    // U
+    crate::tile! {
+        X . X;
+        X X X
+    },
+    // V
+    crate::tile! {
+        . . X;
+        . . X;
+        X X X
+    },
+    // W
+    crate::tile! {
+        . . X;
+        . X X;
+        X X
+    },
+    // X
+    crate::tile! {
+        . X;
+        X X X;
+        . X
+    },
+    // Y
+    crate::tile! {
+        . X;
+        X X;
+        . X;
+        . X
+    },
+    // Z
+    crate::tile! {
+        X X;
+        . X;
+        . X X
+    },
+];
+
+/// The five free tetrominoes: I, O, T, S, and L.
+pub const TETROMINOES: [Tile<4>; 5] = [
+    crate::tile! { 4
+        X X X X
+    },
+    crate::tile! { 4
+        X X . .
+        X X . .
+    },
+    crate::tile! { 4
+        X X X .
+        . X . .
+    },
+    crate::tile! { 4
+        . X X .
+        X X . .
+    },
+    crate::tile! { 4
+        X X X .
+        X . . .
+    },
+];
+
+/// Return the pentomino with the given label under Conway's
+/// labeling scheme (`'O'` through `'Z'`).
+pub fn from_label(label: char) -> Option<Tile<5>> {
+    match label {
+        'O'..='Z' => Some(PENTOMINOES[label as usize - 'O' as usize]),
+        _ => None,
+    }
+}
+
+/// Return one canonical representative of each free polyomino of size `N`,
+/// where canonical means the minimum of its [`Tile::transformations`].
+///
+/// Polyominoes are grown one cell at a time from smaller ones.
+pub fn polyominoes<const N: usize>() -> BTreeSet<Tile<N>> {
+    let mut shapes = BTreeSet::from([Vec::new()]);
+
+    for _ in 0..N {
+        shapes = shapes.iter().flat_map(|shape| grow(shape)).collect();
+    }
+
+    shapes
+        .into_iter()
+        .map(|shape| Tile::new(<[Point; N]>::try_from(shape).unwrap()))
+        .collect()
+}
+
+// Return the canonical forms of all shapes formed by adding one
+// orthogonally adjacent cell to `shape`.
+fn grow(shape: &[Point]) -> Vec<Vec<Point>> {
+    if shape.is_empty() {
+        return vec![vec![Point { i: 0, j: 0 }]];
+    }
+
+    let shape = shape.iter().copied().map(SPoint::from).collect::<Vec<_>>();
+
+    shape
+        .iter()
+        .flat_map(|point| {
+            [(-1, 0), (1, 0), (0, -1), (0, 1)].map(|(di, dj)| point.translate(di, dj))
+        })
+        .filter(|neighbor| {
+            !shape
+                .iter()
+                .any(|point| (point.i, point.j) == (neighbor.i, neighbor.j))
+        })
+        .map(|neighbor| {
+            let mut grown = shape.clone();
+            grown.push(neighbor);
+            canonical(&grown)
+        })
+        .collect()
+}
+
+// Return the minimum clamped image of `shape` under rotation and reflection.
+fn canonical(shape: &[SPoint]) -> Vec<Point> {
+    [
+        shape.to_vec(),
+        shape.iter().map(SPoint::reflect_x).collect(),
+    ]
+    .into_iter()
+    .flat_map(|shape| {
+        [
+            shape.clone(),
+            shape.iter().map(SPoint::rotate_90).collect(),
+            shape.iter().map(SPoint::rotate_180).collect(),
+            shape.iter().map(SPoint::rotate_270).collect::<Vec<_>>(),
+        ]
+    })
+    .map(|shape| {
+        let min_i = shape.iter().map(|point| point.i).min().unwrap_or(0);
+        let min_j = shape.iter().map(|point| point.j).min().unwrap_or(0);
+        let mut shape = shape
+            .iter()
+            .map(|point| Point::from(point.translate(-min_i, -min_j)))
+            .collect::<Vec<_>>();
+        shape.sort();
+        shape
+    })
+    .min()
+    .unwrap_or_default()
+}
+
+/// Board shape to be tiled: the cells of a `rows` by `cols`
+/// rectangle that satisfy some filter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Region {
+    rows: u16,
+    cols: u16,
+    cells: Vec<bool>,
+}
+
+impl Region {
+    pub fn new<F: FnMut(Point) -> bool>(rows: u16, cols: u16, mut filter: F) -> Self {
+        let cells = (0..rows)
+            .flat_map(|i| (0..cols).map(move |j| Point { i, j }))
+            .map(&mut filter)
+            .collect();
+
+        Self { rows, cols, cells }
+    }
+
+    pub fn rectangle(rows: u16, cols: u16) -> Self {
+        Self::new(rows, cols, |_| true)
+    }
+
+    pub fn rows(&self) -> u16 {
+        self.rows
+    }
+
+    pub fn cols(&self) -> u16 {
+        self.cols
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        point.i < self.rows
+            && point.j < self.cols
+            && self.cells[point.i as usize * self.cols as usize + point.j as usize]
+    }
+}
+
+/// Return the number of solutions distinct up to rotation and reflection
+/// for each region, where every piece in `pieces` is used exactly once.
+///
+/// The orientations of each piece are computed once and shared across regions.
+pub fn solve_catalog<const LEN: usize>(regions: &[Region], pieces: &[Tile<LEN>]) -> Vec<usize> {
+    let orientations = pieces.iter().map(TileOrbit::new).collect::<Vec<_>>();
+
+    regions
+        .iter()
+        .map(|region| {
+            let cells = u32::from(region.rows) * u32::from(region.cols);
+            u32::try_from(pieces.len())
+                .ok()
+                .and_then(|pieces| cells.checked_add(pieces))
+                .expect("Region is too large to encode in 32-bit columns");
+
+            let placements = orientations
+                .iter()
+                .enumerate()
+                .flat_map(|(id, tiles)| tiles.iter().map(move |tile| (id, tile)))
+                .flat_map(|(id, tile)| {
+                    (0..region.rows).flat_map(move |di| {
+                        (0..region.cols).filter_map(move |dj| {
+                            let placed = tile.translate(di, dj)?;
+                            placed
+                                .0
+                                .iter()
+                                .all(|point| region.contains(*point))
+                                .then_some(Placement {
+                                    id,
+                                    tile: placed,
+                                    cols: region.cols,
+                                    cells,
+                                })
+                        })
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let mut seen = BTreeSet::new();
+            Solver::new(&placements).solve(|solution| {
+                seen.insert(
+                    solution
+                        .iter()
+                        .map(|index| placements[*index].tile)
+                        .collect::<Set<LEN>>()
+                        .canonicalize(),
+                );
+                ControlFlow::<(), ()>::Continue(())
+            });
+            seen.len()
+        })
+        .collect()
+}
+
+struct Placement<const LEN: usize> {
+    id: usize,
+    tile: Tile<LEN>,
+    cols: u16,
+    cells: u32,
+}
+
+impl<const LEN: usize> solve::Row for Placement<LEN> {
+    fn iter(&self) -> impl Iterator<Item = u32> {
+        self.tile
+            .0
+            .iter()
+            .map(|point| u32::from(point.i) * u32::from(self.cols) + u32::from(point.j))
+            // Encode piece ID after all cells
+            .chain(core::iter::once(self.cells + self.id as u32))
+    }
+}
+
 // Intermediate representation to simplify 2D transformations.
 #[derive(Copy, Clone, Debug)]
 struct SPoint {
-    i: i8,
-    j: i8,
+    i: i32,
+    j: i32,
 }
 
 impl SPoint {
-    fn translate(&self, di: i8, dj: i8) -> Self {
+    fn translate(&self, di: i32, dj: i32) -> Self {
         Self {
             i: self.i + di,
             j: self.j + dj,
         }
     }
 
-    fn reflect_x(&self) -> Self {
+    // Multiply by `m`, treating the point as a column vector `[i, j]`.
+    fn linear(&self, m: [[i8; 2]; 2]) -> Self {
         Self {
-            i: self.i,
-            j: -self.j,
+            i: m[0][0] as i32 * self.i + m[0][1] as i32 * self.j,
+            j: m[1][0] as i32 * self.i + m[1][1] as i32 * self.j,
         }
     }
 
+    fn reflect_x(&self) -> Self {
+        self.linear([[1, 0], [0, -1]])
+    }
+
     fn reflect_y(&self) -> Self {
-        Self {
-            i: -self.i,
-            j: self.j,
-        }
+        self.linear([[-1, 0], [0, 1]])
     }
 
     fn rotate_90(&self) -> Self {
-        Self {
-            i: -self.j,
-            j: self.i,
-        }
+        self.linear([[0, -1], [1, 0]])
     }
 
     fn rotate_180(&self) -> Self {
-        Self {
-            i: -self.i,
-            j: -self.j,
-        }
+        self.linear([[-1, 0], [0, -1]])
     }
 
     fn rotate_270(&self) -> Self {
-        Self {
-            i: self.j,
-            j: -self.i,
-        }
+        self.linear([[0, 1], [-1, 0]])
+    }
+
+    fn transpose(&self) -> Self {
+        self.linear([[0, 1], [1, 0]])
+    }
+
+    fn anti_transpose(&self) -> Self {
+        self.linear([[0, -1], [-1, 0]])
     }
 }
 
@@ -252,26 +1302,111 @@ impl From<Point> for SPoint {
 impl From<SPoint> for Point {
     fn from(SPoint { i, j }: SPoint) -> Self {
         Self {
-            i: i as _,
-            j: j as _,
+            i: u16::try_from(i).expect("Row coordinate out of range"),
+            j: u16::try_from(j).expect("Column coordinate out of range"),
         }
     }
 }
 
+impl<const LEN: usize> Display for Tile<LEN> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.to_grid())
+    }
+}
+
 impl<const LEN: usize> AsRef<[Point; LEN]> for Tile<LEN> {
     fn as_ref(&self) -> &[Point; LEN] {
         &self.0
     }
 }
 
+impl<const LEN: usize> IntoIterator for Tile<LEN> {
+    type Item = Point;
+    type IntoIter = core::array::IntoIter<Point, LEN>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
 impl<const LEN: usize> AsMut<[Point; LEN]> for Tile<LEN> {
     fn as_mut(&mut self) -> &mut [Point; LEN] {
         &mut self.0
     }
 }
 
+#[cfg(feature = "serde")]
+impl<const LEN: usize> serde::Serialize for Set<LEN> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+// Re-establishes the sorted invariant rather than trusting input order.
+#[cfg(feature = "serde")]
+impl<'de, const LEN: usize> serde::Deserialize<'de> for Set<LEN> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<Tile<LEN>>::deserialize(deserializer).map(Set::from_iter)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const LEN: usize> serde::Serialize for Tile<LEN> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.iter())
+    }
+}
+
+// Re-establishes the sorted invariant rather than trusting input order.
+#[cfg(feature = "serde")]
+impl<'de, const LEN: usize> serde::Deserialize<'de> for Tile<LEN> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let points = Vec::<Point>::deserialize(deserializer)?;
+        let len = points.len();
+        <[Point; LEN]>::try_from(points)
+            .map(Tile::new)
+            .map_err(|_| {
+                serde::de::Error::invalid_length(len, &alloc::format!("{} points", LEN).as_str())
+            })
+    }
+}
+
+/// Construct a [`Tile`] from a grid of `X` (filled) and `.` (empty) cells.
+///
+/// Rows are either padded to an explicit leading width, as in
+/// `tile!(3 X X . . X .)`, or separated by `;` with trailing empty cells
+/// omitted, as in `tile!(X X; . X)`. An empty or all-`.` layout
+/// constructs the empty `Tile<0>`.
 #[macro_export]
 macro_rules! tile {
+    () => {
+        $crate::Tile::<0>::new([])
+    };
+
+    (@ragged $i:tt $j:tt [$($acc:expr),*]) => {
+        $crate::Tile::new([$($acc),*])
+    };
+
+    (@ragged $i:tt $j:tt [$($acc:expr),*] X $($rest:tt)*) => {
+        $crate::tile!(@ragged $i ($j + 1) [$($acc ,)* $crate::tile::Point { i: $i, j: $j }] $($rest)*)
+    };
+
+    (@ragged $i:tt $j:tt [$($acc:expr),*] . $($rest:tt)*) => {
+        $crate::tile!(@ragged $i ($j + 1) [$($acc),*] $($rest)*)
+    };
+
+    (@ragged $i:tt $j:tt [$($acc:expr),*] ; $($rest:tt)*) => {
+        $crate::tile!(@ragged ($i + 1) 0 [$($acc),*] $($rest)*)
+    };
+
+    (X $($rest:tt)*) => {
+        $crate::tile!(@ragged 0 0 [] X $($rest)*)
+    };
+
+    (. $($rest:tt)*) => {
+        $crate::tile!(@ragged 0 0 [] . $($rest)*)
+    };
+
     ($width:tt $index:tt: [$($acc:expr),*]) => {
         $crate::Tile::new([$($acc),*])
     };
@@ -288,3 +1423,41 @@ macro_rules! tile {
         $crate::tile!($width 0: [] $($rest)*)
     };
 }
+
+/// Construct a [`Tile`] like [`tile!`](macro@crate::tile), additionally
+/// checking at compile time that the layout has exactly `$len` filled cells.
+///
+/// The layout follows `$len:`, as in `grid!(4: X X; X X)`.
+///
+/// ```
+/// let tile: dancing_links::Tile<4> = dancing_links::grid!(4: X X; X X);
+/// ```
+///
+/// The tile's size comes from the layout, so a wrong `$len` fails only
+/// the count check:
+///
+/// ```compile_fail
+/// let tile: dancing_links::Tile<3> = dancing_links::grid!(4: X X; X);
+/// ```
+#[macro_export]
+macro_rules! grid {
+    (@count) => {
+        0
+    };
+
+    (@count X $($rest:tt)*) => {
+        1 + $crate::grid!(@count $($rest)*)
+    };
+
+    (@count $other:tt $($rest:tt)*) => {
+        $crate::grid!(@count $($rest)*)
+    };
+
+    ($len:literal: $($rest:tt)*) => {{
+        const _: () = assert!(
+            $crate::grid!(@count $($rest)*) == $len,
+            "Number of filled cells doesn't match tile size",
+        );
+        $crate::tile!($($rest)*)
+    }};
+}
@@ -0,0 +1,284 @@
+//! Polycubes, the three-dimensional analogue of [`Tile`](crate::Tile).
+
+use core::cmp::Ordering;
+
+use alloc::vec::Vec;
+
+// Invariant: `self.0` is sorted.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tile3<const LEN: usize>([Point3; LEN]);
+
+impl<const LEN: usize> Tile3<LEN> {
+    pub const fn new(mut points: [Point3; LEN]) -> Self {
+        // Manual bubble sort to preserve `const` compatibility :(
+        'outer: loop {
+            let swap;
+            let mut i = 0;
+
+            while i + 1 < points.len() {
+                match points[i].cmp(&points[i + 1]) {
+                    Ordering::Less | Ordering::Equal => i += 1,
+                    Ordering::Greater => {
+                        swap = points[i];
+                        points[i] = points[i + 1];
+                        points[i + 1] = swap;
+                        continue 'outer;
+                    }
+                }
+            }
+
+            break;
+        }
+
+        Self(points)
+    }
+
+    /// Return the `(rows, cols, layers)` spanned from the origin to the
+    /// tile's furthest point, which is its bounding box once clamped.
+    pub fn dimensions(&self) -> (u16, u16, u16) {
+        let rows = self.0.iter().map(|point| point.i + 1).max().unwrap_or(0);
+        let cols = self.0.iter().map(|point| point.j + 1).max().unwrap_or(0);
+        let layers = self.0.iter().map(|point| point.k + 1).max().unwrap_or(0);
+        (rows, cols, layers)
+    }
+
+    /// Return the distinct rotations of this tile, excluding reflections.
+    ///
+    /// There are at most 24, one for each rotation of the cube.
+    pub fn rotations(&self) -> impl Iterator<Item = Self> {
+        self.orientations(&[SPoint3::identity])
+    }
+
+    /// Return the distinct rotations and reflections of this tile.
+    ///
+    /// There are at most 48, one for each symmetry of the cube.
+    pub fn transformations(&self) -> impl Iterator<Item = Self> {
+        self.orientations(&[SPoint3::identity, SPoint3::reflect])
+    }
+
+    fn orientations(&self, reflections: &[fn(&SPoint3) -> SPoint3]) -> impl Iterator<Item = Self> {
+        let mut orientations = Vec::new();
+
+        for reflect in reflections {
+            // Point the z axis in each of six directions, then
+            // spin about it in each of four ways.
+            for face in 0..6 {
+                for turns in 0..4 {
+                    let tile = self.transform_clamp(|point| {
+                        let mut point = reflect(point);
+                        for _ in 0..turns {
+                            point = point.rotate_z();
+                        }
+                        match face {
+                            0..4 => (0..face).fold(point, |point, _| point.rotate_x()),
+                            4 => point.rotate_y(),
+                            _ => point.rotate_y().rotate_y().rotate_y(),
+                        }
+                    });
+
+                    if !orientations.contains(&tile) {
+                        orientations.push(tile);
+                    }
+                }
+            }
+        }
+
+        orientations.into_iter()
+    }
+
+    fn transform_clamp<F: FnMut(&SPoint3) -> SPoint3>(&self, mut apply: F) -> Self {
+        let tile: [SPoint3; LEN] =
+            core::array::from_fn(|index| apply(&SPoint3::from(self.0[index])));
+
+        let min_i = tile.iter().map(|point| point.i).min().unwrap_or(0);
+        let min_j = tile.iter().map(|point| point.j).min().unwrap_or(0);
+        let min_k = tile.iter().map(|point| point.k).min().unwrap_or(0);
+        let mut tile = core::array::from_fn(|index| {
+            Point3::from(tile[index].translate(-min_i, -min_j, -min_k))
+        });
+        tile.sort();
+        Self(tile)
+    }
+}
+
+impl<const LEN: usize> AsRef<[Point3; LEN]> for Tile3<LEN> {
+    fn as_ref(&self) -> &[Point3; LEN] {
+        &self.0
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point3 {
+    pub i: u16,
+    pub j: u16,
+    pub k: u16,
+}
+
+impl Point3 {
+    const fn cmp(&self, other: &Self) -> Ordering {
+        let lhs = [self.i, self.j, self.k];
+        let rhs = [other.i, other.j, other.k];
+        let mut index = 0;
+
+        while index < 3 {
+            if lhs[index] > rhs[index] {
+                return Ordering::Greater;
+            } else if lhs[index] < rhs[index] {
+                return Ordering::Less;
+            }
+            index += 1;
+        }
+
+        Ordering::Equal
+    }
+}
+
+// Intermediate representation to simplify 3D transformations.
+#[derive(Copy, Clone, Debug)]
+struct SPoint3 {
+    i: i32,
+    j: i32,
+    k: i32,
+}
+
+impl SPoint3 {
+    fn translate(&self, di: i32, dj: i32, dk: i32) -> Self {
+        Self {
+            i: self.i + di,
+            j: self.j + dj,
+            k: self.k + dk,
+        }
+    }
+
+    fn identity(&self) -> Self {
+        *self
+    }
+
+    fn reflect(&self) -> Self {
+        Self {
+            i: self.i,
+            j: self.j,
+            k: -self.k,
+        }
+    }
+
+    fn rotate_x(&self) -> Self {
+        Self {
+            i: self.i,
+            j: -self.k,
+            k: self.j,
+        }
+    }
+
+    fn rotate_y(&self) -> Self {
+        Self {
+            i: self.k,
+            j: self.j,
+            k: -self.i,
+        }
+    }
+
+    fn rotate_z(&self) -> Self {
+        Self {
+            i: -self.j,
+            j: self.i,
+            k: self.k,
+        }
+    }
+}
+
+impl From<Point3> for SPoint3 {
+    fn from(Point3 { i, j, k }: Point3) -> Self {
+        Self {
+            i: i as _,
+            j: j as _,
+            k: k as _,
+        }
+    }
+}
+
+impl From<SPoint3> for Point3 {
+    fn from(SPoint3 { i, j, k }: SPoint3) -> Self {
+        Self {
+            i: u16::try_from(i).expect("Row coordinate out of range"),
+            j: u16::try_from(j).expect("Column coordinate out of range"),
+            k: u16::try_from(k).expect("Layer coordinate out of range"),
+        }
+    }
+}
+
+/// The V tricube, the only Soma cube piece with three cubes.
+pub const SOMA_V: Tile3<3> = crate::tile3! { 2 2
+    X X
+    X .
+};
+
+/// The remaining six Soma cube pieces: the L, T, S, left screw, right screw,
+/// and branch tetracubes.
+pub const SOMA_TETRACUBES: [Tile3<4>; 6] = [
+    // L
+    crate::tile3! { 2 3
+        X X X
+        X . .
+    },
+    // T
+    crate::tile3! { 2 3
+        X X X
+        . X .
+    },
+    // S
+    crate::tile3! { 2 3
+        . X X
+        X X .
+    },
+    // Left screw
+    crate::tile3! { 2 2
+        X X
+        X .
+
+        . X
+        . .
+    },
+    // Right screw
+    crate::tile3! { 2 2
+        X X
+        X .
+
+        . .
+        X .
+    },
+    // Branch
+    crate::tile3! { 2 2
+        X X
+        X .
+
+        X .
+        . .
+    },
+];
+
+/// Construct a [`Tile3`] from `rows` by `cols` layers of `X` (filled) and
+/// `.` (empty) cells, listed one layer after another.
+#[macro_export]
+macro_rules! tile3 {
+    ($rows:tt $cols:tt $index:tt: [$($acc:expr),*]) => {
+        $crate::Tile3::new([$($acc),*])
+    };
+
+    ($rows:tt $cols:tt $index:tt: [$($acc:expr),*] X $($rest:tt)*) => {
+        $crate::tile3!($rows $cols ($index + 1): [$($acc ,)* $crate::tile3::Point3 {
+            i: ($index / $cols) % $rows,
+            j: $index % $cols,
+            k: $index / ($rows * $cols),
+        }] $($rest)*)
+    };
+
+    ($rows:tt $cols:tt $index:tt: [$($acc:expr),*] . $($rest:tt)*) => {
+        $crate::tile3!($rows $cols ($index + 1): [$($acc),*] $($rest)*)
+    };
+
+    ($rows:tt $cols:tt $($rest:tt)*) => {
+        $crate::tile3!($rows $cols 0: [] $($rest)*)
+    };
+}
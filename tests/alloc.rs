@@ -0,0 +1,82 @@
+use core::ops::ControlFlow;
+use std::alloc::GlobalAlloc;
+use std::alloc::Layout;
+use std::alloc::System;
+use std::cell::Cell;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+use dancing_links::solve::SolveScratch;
+use dancing_links::solve::Solver;
+use dancing_links::solve::SolverBuilder;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+thread_local! {
+    static COUNTING: Cell<bool> = const { Cell::new(false) };
+}
+
+struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.with(Cell::get) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+// Count allocations made on this thread while running `f`
+fn allocations<F: FnOnce()>(f: F) -> usize {
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    COUNTING.with(|counting| counting.set(true));
+    f();
+    COUNTING.with(|counting| counting.set(false));
+    ALLOCATIONS.load(Ordering::Relaxed)
+}
+
+#[test]
+fn solve_in() {
+    // Columns `2k` and `2k + 1` are covered by one row or by two, so there
+    // are two choices for each `k`
+    let mut builder = SolverBuilder::new();
+    for k in 0..8 {
+        builder.add_row([2 * k, 2 * k + 1]);
+        builder.add_row([2 * k]);
+        builder.add_row([2 * k + 1]);
+    }
+    let solver = builder.build();
+
+    let mut scratch = SolveScratch::new();
+    let mut count = 0;
+    let mut solve = |solver: &Solver, scratch: &mut SolveScratch| {
+        solver.solve_in(scratch, |_| {
+            count += 1;
+            ControlFlow::<()>::Continue(())
+        });
+    };
+
+    // Grow the buffers to fit the deepest solution
+    solve(&solver, &mut scratch);
+
+    let clone = solver.clone();
+    assert_eq!(allocations(|| solve(&clone, &mut scratch)), 0);
+    assert_eq!(allocations(|| solve(&solver, &mut scratch)), 0);
+    assert_eq!(count, 3 << 8);
+    assert_eq!(solver.solve_count(), 1 << 8);
+
+    // Fresh buffers allocate
+    assert!(
+        allocations(|| {
+            solver.solve(|_| ControlFlow::<()>::Continue(()));
+        }) > 0
+    );
+}
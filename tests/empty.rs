@@ -0,0 +1,55 @@
+use dancing_links::tile;
+use dancing_links::Tile;
+
+#[test]
+fn tile() {
+    let empty: Tile<0> = Tile::new([]);
+
+    assert_eq!(dancing_links::tile!(), empty);
+    assert_eq!(dancing_links::tile!(. .; .), empty);
+    assert_eq!(dancing_links::tile!(2 . . . .), empty);
+    assert_eq!(Tile::<0>::from_grid("").unwrap(), empty);
+    assert_eq!(Tile::<0>::from_grid("..\n..").unwrap(), empty);
+
+    assert_eq!(empty.area(), 0);
+    assert_eq!(empty.dimensions(), (0, 0));
+    assert_eq!(empty.to_grid(), "");
+    assert!(empty.is_connected());
+    assert_eq!(empty.normalized(), empty);
+
+    assert_eq!(empty.rotate_90(), empty);
+    assert_eq!(empty.reflect_x(), empty);
+    assert_eq!(empty.transpose(), empty);
+    assert_eq!(empty.rotations().collect::<Vec<_>>(), [empty]);
+    assert!(empty.transformations().all(|tile| tile == empty));
+
+    let mut tile = empty;
+    tile.rotate_90_in_place();
+    tile.anti_transpose_in_place();
+    assert_eq!(tile, empty);
+
+    assert_eq!(
+        tile::polyominoes::<0>().into_iter().collect::<Vec<_>>(),
+        [empty]
+    );
+}
+
+#[test]
+fn set() {
+    let empty = tile::Set::<5>::new();
+
+    assert_eq!(empty.bounding_box(), (0, 0));
+    assert_eq!(empty.render(0, 0), "");
+    assert_eq!(empty.rotate_90(), empty);
+    assert_eq!(empty.reflect_y(), empty);
+    assert_eq!(empty.canonicalize(), empty);
+    assert!(empty.tiles_exactly(0, 0, |_| true));
+    assert!(!empty.tiles_exactly(1, 1, |_| true));
+
+    let set = [Tile::<0>::new([]); 3]
+        .into_iter()
+        .collect::<tile::Set<0>>();
+    assert_eq!(set.bounding_box(), (0, 0));
+    assert_eq!(set.rotate_180(), set);
+    assert_eq!(set.canonicalize(), set);
+}
@@ -0,0 +1,68 @@
+use std::collections::BTreeSet;
+
+use dancing_links::hex::HexSet;
+use dancing_links::hex_tile;
+use dancing_links::HexTile;
+
+#[test]
+fn orientations() {
+    let line = hex_tile!(X X X);
+    let triangle = hex_tile!(X X; X);
+    let bent = hex_tile!(X X; . X);
+
+    assert_eq!(line.rotations().count(), 3);
+    assert_eq!(triangle.rotations().count(), 2);
+    assert_eq!(bent.rotations().count(), 6);
+
+    // Every trihex is its own mirror image
+    for tile in [line, triangle, bent] {
+        assert_eq!(
+            tile.rotations().collect::<BTreeSet<_>>(),
+            tile.transformations().collect::<BTreeSet<_>>(),
+        );
+        assert_eq!((0..6).fold(tile, |tile, _| tile.rotate_60()), tile);
+        assert_eq!(tile.reflect().reflect(), tile);
+    }
+
+    // The 11 fixed trihexes, and the 3 free ones
+    let fixed = [line, triangle, bent]
+        .iter()
+        .flat_map(HexTile::transformations)
+        .collect::<BTreeSet<_>>();
+    assert_eq!(fixed.len(), 11);
+    assert_eq!(
+        fixed
+            .iter()
+            .map(|tile| tile.transformations().min().unwrap())
+            .collect::<BTreeSet<_>>()
+            .len(),
+        3,
+    );
+
+    // An asymmetric tetrahex has a distinct mirror image
+    let chiral = hex_tile!(X X X; . . X);
+    assert_eq!(chiral.rotations().count(), 6);
+    assert_eq!(chiral.transformations().count(), 12);
+    assert!(!chiral.rotations().any(|tile| tile == chiral.reflect()));
+}
+
+#[test]
+fn canonicalize() {
+    let set = [hex_tile!(X X X), hex_tile!(. . . X; X X)]
+        .into_iter()
+        .collect::<HexSet<3>>();
+
+    let canonical = set.canonicalize();
+    assert_eq!(canonical.canonicalize(), canonical);
+    assert_eq!(set.rotate_60().canonicalize(), canonical);
+    assert_eq!(
+        set.reflect().rotate_60().rotate_60().canonicalize(),
+        canonical
+    );
+
+    // Moving one tile changes the shape of the set
+    let moved = [hex_tile!(X X X), hex_tile!(. . . . X; X X)]
+        .into_iter()
+        .collect::<HexSet<3>>();
+    assert_ne!(moved.canonicalize(), canonical);
+}
@@ -0,0 +1,37 @@
+use dancing_links::nqueens;
+
+#[test]
+fn count() {
+    // https://oeis.org/A000170
+    let expected = [1, 1, 0, 0, 2, 10, 4, 40, 92, 352];
+    let actual = (0..expected.len()).map(nqueens::count).collect::<Vec<_>>();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn solutions() {
+    let mut count = 0;
+    for solution in nqueens::solutions(8) {
+        count += 1;
+        for (a, file_a) in solution.iter().enumerate() {
+            for (b, file_b) in solution.iter().enumerate().skip(a + 1) {
+                assert_ne!(file_a, file_b);
+                assert_ne!(file_a.abs_diff(*file_b), a.abs_diff(b));
+            }
+        }
+    }
+
+    // https://oeis.org/A000170
+    assert_eq!(count, 92);
+
+    assert_eq!(
+        nqueens::solutions(4).collect::<Vec<_>>(),
+        [[1, 3, 0, 2], [2, 0, 3, 1]]
+    );
+
+    // Stopping early leaves the rest of the search undone
+    assert_eq!(
+        nqueens::solutions(30).next().map(|solution| solution.len()),
+        Some(30)
+    );
+}
@@ -5,12 +5,17 @@
 
 use core::ops::ControlFlow;
 use std::collections::BTreeSet;
-use std::collections::HashMap;
 
+use dancing_links::packing;
+use dancing_links::solve;
+use dancing_links::solve::Identified;
 use dancing_links::solve::Row;
 use dancing_links::solve::Solver;
 use dancing_links::tile;
+use dancing_links::tile::IdSet;
 use dancing_links::tile::Point;
+use dancing_links::tile::Region;
+use dancing_links::tile::TileOrbit;
 use dancing_links::Tile;
 
 #[derive(Copy, Clone, Debug, PartialOrd, Ord, PartialEq, Eq, Hash)]
@@ -23,7 +28,7 @@ macro_rules! pentomino {
     ($id:tt: $($rest:tt)*) => {
         Pentomino {
             id: $id,
-            tile: tile!(5 $($rest)*),
+            tile: tile!($($rest)*),
         }
     };
 }
@@ -36,61 +41,59 @@ const PENTOMINOES: [Pentomino; 12] = [
         X X X X X
     },
     pentomino! { 'P':
-        X X . . .
-        X X . . .
-        X . . . .
+        X X;
+        X X;
+        X
     },
     pentomino! { 'Q':
-        X X X X .
-        . . . X .
-        . . . . .
-        . . . . .
+        X X X X;
+        . . . X
     },
     pentomino! { 'R':
-        . X X . .
-        X X . . .
-        . X . . .
+        . X X;
+        X X;
+        . X
     },
     pentomino! { 'S':
-        X . . . .
-        X X . . .
-        . X . . .
-        . X . . .
+        X;
+        X X;
+        . X;
+        . X
     },
     pentomino! { 'T':
-        X X X . .
-        . X . . .
-        . X . . .
+        X X X;
+        . X;
+        . X
     },
     pentomino! { 'U':
-        X . X . .
-        X X X . .
+        X . X;
+        X X X
     },
     pentomino! { 'V':
-        . . X . .
-        . . X . .
-        X X X . .
+        . . X;
+        . . X;
+        X X X
     },
     pentomino! { 'W':
-        . . X . .
-        . X X . .
-        X X . . .
+        . . X;
+        . X X;
+        X X
     },
     pentomino! { 'X':
-        . X . . .
-        X X X . .
-        . X . . .
+        . X;
+        X X X;
+        . X
     },
     pentomino! { 'Y':
-        . X . . .
-        X X . . .
-        . X . . .
-        . X . . .
+        . X;
+        X X;
+        . X;
+        . X
     },
     pentomino! { 'Z':
-        X X . . .
-        . X . . .
-        . X X . .
+        X X;
+        . X;
+        . X X
     },
 ];
 
@@ -106,11 +109,7 @@ fn transform() {
             id => unreachable!("Unexpected pentomino ID: {}", id),
         };
 
-        let actual = pentomino
-            .tile
-            .transformations()
-            .collect::<BTreeSet<_>>()
-            .len();
+        let actual = pentomino.tile.transformations().count();
 
         assert_eq!(
             expected, actual,
@@ -120,30 +119,651 @@ fn transform() {
     }
 }
 
+#[test]
+fn transform_offset() {
+    // The identity image is clamped like the other seven
+    let domino = tile! {
+        . X;
+        . X
+    };
+    assert_eq!(domino.transformations().count(), 2);
+    assert_eq!(domino.rotations().count(), 2);
+    assert!(domino
+        .transformations_raw()
+        .all(|tile| tile == tile.normalized()));
+
+    let x = tile::from_label('X').unwrap();
+    let shifted = Tile::new(x.as_ref().map(|point| point.translate(1, 1).unwrap()));
+    assert_eq!(shifted.transformations().count(), 1);
+    assert_eq!(TileOrbit::new(&shifted).as_ref(), [x]);
+
+    // Each domino placement appears once
+    let placements = packing::place_all(&[domino], 2, 2, |_| true);
+    assert_eq!(placements.len(), 4);
+}
+
+#[test]
+fn is_symmetric_to() {
+    use dancing_links::tile::Transform;
+
+    let l = tile::from_label('Q').unwrap();
+    for transform in Transform::ALL {
+        let image = transform.apply(&l);
+        assert_eq!(l.is_symmetric_to(&image), Some(transform));
+    }
+
+    let offset = Tile::new(l.as_ref().map(|point| point.translate(1, 2).unwrap()));
+    assert_eq!(l.is_symmetric_to(&offset), Some(Transform::Identity));
+
+    // Symmetric pieces report the first matching transform
+    let x = tile::from_label('X').unwrap();
+    assert_eq!(x.is_symmetric_to(&x.rotate_90()), Some(Transform::Identity));
+
+    let o = tile::from_label('O').unwrap();
+    assert_eq!(o.is_symmetric_to(&o.transpose()), Some(Transform::Rotate90));
+    assert_eq!(o.is_symmetric_to(&l), None);
+}
+
+#[test]
+fn transpose() {
+    for pentomino in PENTOMINOES {
+        let tile = pentomino.tile;
+        assert_eq!(tile.transpose(), tile.reflect_x().rotate_90());
+        assert_eq!(tile.anti_transpose(), tile.reflect_x().rotate_270());
+        assert_eq!(tile.transpose().transpose(), tile);
+        assert_eq!(tile.anti_transpose(), tile.transpose().rotate_180());
+    }
+
+    let set = tile::PENTOMINOES
+        .into_iter()
+        .take(3)
+        .collect::<tile::Set<5>>();
+    assert_eq!(set.transpose(), set.reflect_x().rotate_90());
+    assert_eq!(set.anti_transpose(), set.reflect_x().rotate_270());
+}
+
+#[test]
+fn linear() {
+    for pentomino in PENTOMINOES {
+        let tile = pentomino.tile;
+        assert_eq!(tile.transform_linear([[1, 0], [0, 1]]), tile);
+        assert_eq!(tile.transform_linear([[0, -1], [1, 0]]), tile.rotate_90());
+        assert_eq!(tile.transform_linear([[1, 0], [0, -1]]), tile.reflect_x());
+        assert_eq!(tile.transform_linear([[0, 1], [1, 0]]), tile.transpose());
+    }
+
+    assert_eq!(
+        tile!(3 X X X).transform_linear([[1, 1], [0, 1]]),
+        tile! { 3
+            X . .
+            . X .
+            . . X
+        },
+    );
+}
+
+#[test]
+fn ragged() {
+    assert_eq!(tile!(X X; . X X), tile!(3 X X . . X X));
+    assert_eq!(
+        tile! {
+            . X;
+            X X X;
+            . X
+        },
+        tile::from_label('X').unwrap(),
+    );
+}
+
+#[test]
+fn translate() {
+    let point = Point { i: 2, j: 3 };
+    assert_eq!(point.translate(1, -3), Some(Point { i: 3, j: 0 }));
+    assert_eq!(point.translate(-3, 0), None);
+    assert_eq!(point.translate(0, -4), None);
+    assert_eq!(Point { i: u16::MAX, j: 0 }.translate(1, 0), None,);
+    assert_eq!(point.translate(i32::MAX, 0), None);
+}
+
+#[test]
+fn normalized() {
+    let x = tile::from_label('X').unwrap();
+    let offset = Tile::new(x.as_ref().map(|point| point.translate(2, 3).unwrap()));
+
+    assert_ne!(offset, x);
+    assert_eq!(offset.normalized(), x);
+    assert!(offset.same_shape(&x));
+    assert!(!offset.same_shape(&tile::from_label('O').unwrap()));
+    assert_eq!(x.normalized(), x);
+}
+
+#[test]
+fn label() {
+    assert_eq!(
+        tile::from_label('X'),
+        Some(tile! { 5
+            . X . . .
+            X X X . .
+            . X . . .
+        }),
+    );
+
+    for pentomino in PENTOMINOES {
+        assert_eq!(tile::from_label(pentomino.id), Some(pentomino.tile));
+    }
+
+    assert_eq!(tile::from_label('A'), None);
+}
+
+#[test]
+fn grid() {
+    for pentomino in PENTOMINOES {
+        let grid = pentomino
+            .tile
+            .cells()
+            .fold([['.'; 5]; 5], |mut grid, point| {
+                grid[point.i as usize][point.j as usize] = 'X';
+                grid
+            })
+            .map(|row| row.iter().collect::<String>() + "  \n")
+            .concat();
+
+        assert_eq!(Tile::<5>::from_grid(&grid), Ok(pentomino.tile));
+    }
+
+    let grid = "
+        . X .
+        X X X
+        . X .
+    ";
+    assert_eq!(
+        Tile::<5>::from_grid(grid),
+        Ok(tile::from_label('X').unwrap())
+    );
+
+    assert_eq!(
+        Tile::<4>::from_grid(grid),
+        Err(tile::ParseTileError::Count {
+            expected: 4,
+            actual: 5
+        }),
+    );
+    assert_eq!(
+        Tile::<1>::from_grid("X o"),
+        Err(tile::ParseTileError::InvalidCell('o')),
+    );
+}
+
+#[test]
+fn render() {
+    let x = tile::from_label('X').unwrap();
+    assert_eq!(x.to_grid(), ".X.\nXXX\n.X.\n");
+    assert_eq!(x.to_string(), x.to_grid());
+
+    for pentomino in PENTOMINOES {
+        assert_eq!(
+            Tile::from_grid(&pentomino.tile.to_grid()),
+            Ok(pentomino.tile)
+        );
+    }
+
+    let pentominoes = pack(3, 20, |_| true);
+    let solution = Solver::new(&pentominoes)
+        .solve_mapped(&pentominoes, |solution| {
+            ControlFlow::Break(
+                solution
+                    .iter()
+                    .map(|pentomino| pentomino.tile)
+                    .collect::<tile::Set<5>>(),
+            )
+        })
+        .unwrap();
+
+    assert_eq!(solution.bounding_box(), (3, 20));
+    assert_eq!(solution.rotate_90().bounding_box(), (20, 3));
+    assert!(solution.iter().all(|tile| tile.area() == 5));
+    assert_eq!(tile::Set::<5>::new().bounding_box(), (0, 0));
+
+    let render = solution.render(3, 20);
+
+    assert_eq!(render.lines().count(), 3);
+    assert!(render.lines().all(|line| line.len() == 20));
+    assert_eq!(
+        render
+            .chars()
+            .filter(|char| *char != '\n')
+            .collect::<BTreeSet<_>>(),
+        ('A'..='L').collect(),
+    );
+}
+
+#[test]
+fn extend() {
+    let tiles = PENTOMINOES.map(|pentomino| pentomino.tile);
+
+    let mut pushed = tile::Set::new();
+    tiles.iter().rev().for_each(|tile| pushed.push(*tile));
+
+    let mut extended = tile::Set::with_capacity(tiles.len());
+    extended.extend(tiles.iter().rev().copied());
+
+    assert_eq!(extended, pushed);
+    assert_eq!(extended, tiles.into_iter().collect());
+}
+
+#[test]
+fn map() {
+    let tiles = PENTOMINOES.map(|pentomino| pentomino.tile);
+    let set = tiles.into_iter().collect::<tile::Set<5>>();
+
+    // Shifting every tile right by its index reorders them
+    let mut index = 0;
+    let shifted = set.map(|tile| {
+        index += 1;
+        let points = *tile.as_ref();
+        Tile::new(points.map(|point| point.translate(0, 12 - index).expect("Point out of range")))
+    });
+
+    assert!(shifted.iter().is_sorted());
+    assert_eq!(shifted.iter().count(), 12);
+    assert_ne!(
+        shifted.iter().collect::<Vec<_>>(),
+        set.iter().collect::<Vec<_>>()
+    );
+    assert_eq!(set.map(|tile| *tile), set);
+}
+
+#[test]
+fn canonicalize_orbit() {
+    let mut rng = indices(0x5eed);
+
+    // Rotating twice by 180 degrees clamps to the origin without moving
+    let symmetries: [fn(&tile::Set<5>) -> tile::Set<5>; 8] = [
+        |set| set.rotate_180().rotate_180(),
+        tile::Set::rotate_90,
+        tile::Set::rotate_180,
+        tile::Set::rotate_270,
+        tile::Set::reflect_x,
+        tile::Set::reflect_y,
+        tile::Set::transpose,
+        tile::Set::anti_transpose,
+    ];
+
+    for _ in 0..200 {
+        // Randomly oriented pentominoes at random offsets, overlapping or not
+        let set = (0..1 + rng(4))
+            .map(|_| {
+                let tile = PENTOMINOES[rng(12)].tile;
+                let orientations = tile.transformations().collect::<Vec<_>>();
+                let (di, dj) = (rng(8) as i32, rng(8) as i32);
+                let points = *orientations[rng(orientations.len())].as_ref();
+                Tile::new(points.map(|point| point.translate(di, dj).expect("Point out of range")))
+            })
+            .collect::<tile::Set<5>>();
+
+        let canonical = set.canonicalize();
+        assert_eq!(canonical.canonicalize(), canonical);
+        assert!(symmetries
+            .iter()
+            .any(|symmetry| symmetry(&set) == canonical));
+
+        for symmetry in symmetries {
+            assert_eq!(symmetry(&set).canonicalize(), canonical);
+        }
+    }
+}
+
+#[test]
+fn id_set() {
+    let pentominoes = pack(3, 20, |_| true);
+    let solution = Solver::new(&pentominoes)
+        .solve_mapped(&pentominoes, |solution| {
+            ControlFlow::Break(
+                solution
+                    .iter()
+                    .map(|pentomino| (pentomino.id, pentomino.tile))
+                    .collect::<IdSet<char, 5>>(),
+            )
+        })
+        .unwrap();
+
+    // Each cell is labeled by the piece covering it
+    let render = solution.render(3, 20);
+    for (id, tile) in solution.iter() {
+        for point in tile.as_ref() {
+            let line = render.lines().nth(point.i as usize).unwrap();
+            assert_eq!(line.chars().nth(point.j as usize), Some(*id));
+        }
+    }
+
+    // Identifiers follow their tiles through transformations
+    let rotated = solution.rotate_180();
+    assert_eq!(rotated.bounding_box(), (3, 20));
+    assert_eq!(rotated.to_set(), solution.to_set().rotate_180());
+    assert_eq!(rotated.canonicalize(), solution.canonicalize());
+    assert_eq!(
+        rotated.render(3, 20),
+        render
+            .lines()
+            .rev()
+            .map(|line| line.chars().rev().chain(['\n']).collect::<String>())
+            .collect::<String>(),
+    );
+}
+
+#[test]
+fn tiles_exactly() {
+    let pentominoes = pack(3, 20, |_| true);
+    let solution = Solver::new(&pentominoes)
+        .solve_mapped(&pentominoes, |solution| {
+            ControlFlow::Break(
+                solution
+                    .iter()
+                    .map(|pentomino| pentomino.tile)
+                    .collect::<tile::Set<5>>(),
+            )
+        })
+        .unwrap();
+
+    assert!(solution.tiles_exactly(3, 20, |_| true));
+    assert!(!solution.tiles_exactly(3, 21, |_| true));
+    assert!(!solution.tiles_exactly(3, 19, |_| true));
+    assert!(!solution.tiles_exactly(3, 20, |point| point != Point { i: 0, j: 0 }));
+
+    // Overlapping tiles cover the same number of cells but leave a gap
+    let mut overlapping = solution.iter().skip(1).copied().collect::<tile::Set<5>>();
+    overlapping.push(*solution.iter().nth(1).unwrap());
+    assert!(!overlapping.tiles_exactly(3, 20, |_| true));
+}
+
+#[test]
+fn grid_macro() {
+    const P: Tile<5> = dancing_links::grid!(5: X X; X X; X);
+    assert_eq!(Some(P), tile::from_label('P'));
+    assert_eq!(
+        dancing_links::grid!(5: 3 . X . X X X . X .),
+        tile::from_label('X').unwrap()
+    );
+}
+
+#[test]
+fn in_place() {
+    type Transform = (fn(&Tile<5>) -> Tile<5>, fn(&mut Tile<5>));
+
+    let transforms: [Transform; 7] = [
+        (Tile::reflect_x, Tile::reflect_x_in_place),
+        (Tile::reflect_y, Tile::reflect_y_in_place),
+        (Tile::rotate_90, Tile::rotate_90_in_place),
+        (Tile::rotate_180, Tile::rotate_180_in_place),
+        (Tile::rotate_270, Tile::rotate_270_in_place),
+        (Tile::transpose, Tile::transpose_in_place),
+        (Tile::anti_transpose, Tile::anti_transpose_in_place),
+    ];
+
+    for tile in tile::PENTOMINOES.iter().flat_map(Tile::transformations) {
+        for (copy, in_place) in transforms {
+            let mut mutated = tile;
+            in_place(&mut mutated);
+            assert_eq!(mutated, copy(&tile));
+        }
+    }
+}
+
+#[test]
+fn library() {
+    assert_eq!(
+        PENTOMINOES.map(|pentomino| pentomino.tile),
+        tile::PENTOMINOES
+    );
+
+    let total = tile::PENTOMINOES
+        .iter()
+        .map(|tile| tile.transformations().count())
+        .sum::<usize>();
+
+    assert_eq!(total, 63);
+
+    for tile in tile::PENTOMINOES {
+        let orbit = TileOrbit::new(&tile);
+        assert_eq!(orbit.as_ref(), tile.transformations().collect::<Vec<_>>(),);
+        assert!(tile.transformations().all(|tile| orbit.contains(&tile)));
+    }
+}
+
+#[test]
+fn rotations() {
+    let s = tile::from_label('S').unwrap();
+    assert_eq!(s.rotations().count(), 4);
+    assert_eq!(s.transformations().count(), 8);
+    assert_eq!(s.transformations_raw().count(), 8);
+    assert_eq!(s.distinct_transformations().len(), 8);
+
+    // https://en.wikipedia.org/wiki/Pentomino#Symmetry
+    let one_sided = tile::PENTOMINOES
+        .iter()
+        .flat_map(|tile| tile.transformations())
+        .map(|tile| tile.rotations().min().unwrap())
+        .collect::<BTreeSet<_>>();
+    assert_eq!(one_sided.len(), 18);
+
+    let set = [s, tile::from_label('O').unwrap()]
+        .into_iter()
+        .collect::<tile::Set<5>>();
+    assert_eq!(set.rotations().count(), 4);
+    assert_eq!(set.distinct_transformations().len(), 8);
+
+    let x = [tile::from_label('X').unwrap()]
+        .into_iter()
+        .collect::<tile::Set<5>>();
+    assert_eq!(x.distinct_transformations().len(), 1);
+
+    // Offset sets are clamped before comparing orientations
+    let shifted =
+        x.map(|tile| Tile::new(tile.as_ref().map(|point| point.translate(1, 1).unwrap())));
+    assert_eq!(
+        shifted.distinct_transformations(),
+        BTreeSet::from([x.clone()])
+    );
+    assert_eq!(shifted.rotations().collect::<Vec<_>>(), [x.clone()]);
+    assert_eq!(shifted.canonicalize(), x);
+
+    let shifted =
+        set.map(|tile| Tile::new(tile.as_ref().map(|point| point.translate(2, 0).unwrap())));
+    assert_eq!(
+        shifted.distinct_transformations(),
+        set.distinct_transformations()
+    );
+    assert_eq!(shifted.rotations().count(), 4);
+}
+
+#[test]
+fn dimensions() {
+    let o = tile::from_label('O').unwrap();
+    assert_eq!(o.dimensions(), (1, 5));
+    assert_eq!(o.rotate_90().dimensions(), (5, 1));
+    assert!(o.contains(Point { i: 0, j: 4 }));
+    assert!(o.cells().eq(o.as_ref().iter().copied()));
+    assert!(o.into_iter().eq(o.cells()));
+    assert!(!o.contains(Point { i: 1, j: 0 }));
+
+    let x = tile::from_label('X').unwrap();
+    assert_eq!(x.dimensions(), (3, 3));
+    assert!(x.contains(Point { i: 1, j: 1 }));
+    assert!(!x.contains(Point { i: 0, j: 0 }));
+}
+
+#[test]
+fn connected() {
+    for tile in tile::PENTOMINOES {
+        assert!(tile.is_connected());
+        assert!(tile.is_connected_diagonal());
+    }
+
+    let gap = tile! { 3
+        X . X
+    };
+    assert!(!gap.is_connected());
+    assert!(!gap.is_connected_diagonal());
+
+    let diagonal = tile! { 3
+        X X .
+        . . X
+    };
+    assert!(!diagonal.is_connected());
+    assert!(diagonal.is_connected_diagonal());
+}
+
+#[test]
+fn algebra() {
+    let set = |labels: &str| {
+        labels
+            .chars()
+            .filter_map(tile::from_label)
+            .collect::<tile::Set<5>>()
+    };
+
+    let a = set("OPQRST");
+    let b = set("RSTUVW");
+
+    assert_eq!(a.union(&b), set("OPQRSTUVW"));
+    assert_eq!(a.intersection(&b), set("RST"));
+    assert_eq!(a.difference(&b), set("OPQ"));
+    assert_eq!(b.difference(&a), set("UVW"));
+    assert_eq!(a.union(&a), a);
+    assert_eq!(a.difference(&a), tile::Set::new());
+}
+
+#[test]
+fn wide() {
+    let mut o = tile::from_label('O').unwrap();
+    for point in o.as_mut() {
+        point.i += 1_000;
+        point.j += 300;
+    }
+
+    assert_eq!(o.rotate_90(), tile::from_label('O').unwrap().rotate_90());
+    assert_eq!(o.dimensions(), (1_001, 305));
+}
+
 impl Pentomino {
-    fn encode_id(&self) -> u16 {
-        (self.id as u8 - b'O') as u16
+    fn encode_id(&self) -> u32 {
+        (self.id as u8 - b'O') as u32
     }
 }
 
 impl Row for Pentomino {
-    fn iter(&self) -> impl Iterator<Item = u16> {
+    fn iter(&self) -> impl Iterator<Item = u32> {
         self.tile
-            .as_ref()
-            .iter()
+            .cells()
             // Imposes maximum width of 32 units
-            .map(|point| point.i as u16 * 32 + point.j as u16)
+            .map(|point| {
+                solve::pack_coords(point.i.into(), point.j.into(), 32).expect("Point out of range")
+            })
             // Encode tile ID in upper 4 bits
             // Note: offset by 1 to avoid collision with (0, 0) point encoding
             .chain(core::iter::once((1 + self.encode_id()) << 12))
     }
 }
 
+impl Identified for Pentomino {
+    type Id = char;
+
+    fn id(&self) -> char {
+        self.id
+    }
+}
+
+#[test]
+fn ids_3x20() {
+    let pentominoes = pack(3, 20, |_| true);
+    let solver = Solver::new(&pentominoes);
+
+    let mut count = 0;
+    solver.solve_ids(&pentominoes, |ids| {
+        let mut ids = ids.to_vec();
+        ids.sort();
+        assert_eq!(ids, ('O'..='Z').collect::<Vec<_>>());
+        count += 1;
+        ControlFlow::<(), _>::Continue(())
+    });
+
+    assert_eq!(count, solver.solve_count());
+}
+
 #[test]
 fn rectangle_6x10() {
     assert_eq!(rectangle(6, 10).len(), 2_339);
 }
 
+#[test]
+fn fundamental_domain() {
+    let tiles = PENTOMINOES.map(|pentomino| pentomino.tile);
+    let placements = packing::place_all(&tiles, 6, 10, |_| true);
+    let x = PENTOMINOES
+        .iter()
+        .position(|pentomino| pentomino.id == 'X')
+        .unwrap();
+
+    // The X's 32 placements fall into 8 orbits of 4
+    let constrained = packing::constrain_to_fundamental_domain(&placements, x, 6, 10);
+    let count =
+        |placements: &[(usize, Tile<5>)]| placements.iter().filter(|(id, _)| *id == x).count();
+    assert_eq!(count(&placements), 32);
+    assert_eq!(count(&constrained), 8);
+
+    let pentominoes = constrained
+        .into_iter()
+        .map(|(index, tile)| Pentomino {
+            id: PENTOMINOES[index].id,
+            tile,
+        })
+        .collect::<Vec<_>>();
+
+    let mut seen = BTreeSet::new();
+    let (_, stats) = Solver::new(&pentominoes).solve_with_stats(|solution| {
+        let set = solution
+            .iter()
+            .map(|row| pentominoes[*row].tile)
+            .collect::<tile::Set<5>>();
+        assert!(seen.insert(set.canonicalize()));
+        ControlFlow::<()>::Continue(())
+    });
+
+    // Every solution is its own symmetry class, with no duplicates
+    assert_eq!(stats.solutions, 2_339);
+    assert_eq!(seen.len(), 2_339);
+}
+
+#[test]
+fn fundamental_domain_square() {
+    let tiles = PENTOMINOES.map(|pentomino| pentomino.tile);
+    let placements = packing::place_all(&tiles, 8, 8, |_| true);
+    let r = PENTOMINOES
+        .iter()
+        .position(|pentomino| pentomino.id == 'R')
+        .unwrap();
+
+    // The R has no symmetry of its own, so its orbits all have size 8
+    let constrained = packing::constrain_to_fundamental_domain(&placements, r, 8, 8);
+    let count =
+        |placements: &[(usize, Tile<5>)]| placements.iter().filter(|(id, _)| *id == r).count();
+    assert_eq!(count(&placements), 8 * count(&constrained));
+}
+
+#[test]
+#[should_panic(expected = "is fixed by a board symmetry")]
+fn fundamental_domain_diagonal() {
+    let tiles = PENTOMINOES.map(|pentomino| pentomino.tile);
+    let placements = packing::place_all(&tiles, 8, 8, |_| true);
+    let x = PENTOMINOES
+        .iter()
+        .position(|pentomino| pentomino.id == 'X')
+        .unwrap();
+
+    // The X can sit centered on the main diagonal
+    packing::constrain_to_fundamental_domain(&placements, x, 8, 8);
+}
+
 #[test]
 fn rectangle_5x12() {
     assert_eq!(rectangle(5, 12).len(), 1_010);
@@ -159,6 +779,74 @@ fn rectangle_3x20() {
     assert_eq!(rectangle(3, 20).len(), 2);
 }
 
+#[test]
+fn tiebreak() {
+    let pentominoes = pack(3, 20, |_| true);
+
+    let mut solver = Solver::new(&pentominoes);
+    let leftmost = stats(&solver);
+
+    solver.set_tiebreak(|a, b| b.cmp(&a));
+    let rightmost = stats(&solver);
+
+    assert_eq!(leftmost.solutions, 8);
+    assert_eq!(rightmost.solutions, 8);
+    assert_ne!(leftmost.nodes, rightmost.nodes);
+}
+
+#[test]
+fn column_priority() {
+    // Branch on cells nearest the middle column first
+    let distance = |sparse: u32| match sparse {
+        sparse if sparse < 1 << 12 => (sparse % 32).abs_diff(10),
+        _ => u32::MAX,
+    };
+    let prioritize = |solver: &mut Solver| {
+        let priority = (0..solver.column_count() as u32)
+            .map(|dense| distance(solver.sparse_column(dense)))
+            .collect();
+        solver.set_column_priority(priority);
+    };
+
+    let pentominoes = pack(3, 20, |_| true);
+    let mut solver = Solver::new(&pentominoes);
+    let leftmost = stats(&solver);
+    prioritize(&mut solver);
+    let center = stats(&solver);
+
+    assert_eq!(leftmost.solutions, 8);
+    assert_eq!(center.solutions, 8);
+    assert_ne!(leftmost.nodes, center.nodes);
+
+    // Without the X, the four corners tie for fewest placements
+    let pentominoes = pentominoes
+        .into_iter()
+        .filter(|pentomino| pentomino.id != 'X')
+        .collect::<Vec<_>>();
+    let first_cover = |solver: &Solver| {
+        solver.solve_trace(|event| match event {
+            solve::Event::Cover(sparse) => ControlFlow::Break(sparse),
+            _ => ControlFlow::Continue(()),
+        })
+    };
+
+    let mut solver = Solver::new(&pentominoes);
+    assert_eq!(first_cover(&solver), Some(0));
+    prioritize(&mut solver);
+    assert_eq!(first_cover(&solver), Some(19));
+}
+
+#[test]
+fn catalog() {
+    let regions =
+        [(6, 10), (5, 12), (4, 15), (3, 20)].map(|(rows, cols)| Region::rectangle(rows, cols));
+    let pieces = PENTOMINOES.map(|pentomino| pentomino.tile);
+    assert_eq!(
+        tile::solve_catalog(&regions, &pieces),
+        [2_339, 1_010, 368, 2],
+    );
+}
+
 #[test]
 fn scott() {
     assert_eq!(
@@ -169,11 +857,113 @@ fn scott() {
     );
 }
 
-fn rectangle(rows: u8, cols: u8) -> BTreeSet<tile::Set<5>> {
+#[test]
+fn scott_incremental() {
+    let pentominoes = pack(8, 8, |point| {
+        !((3..5).contains(&point.i) && (3..5).contains(&point.j))
+    });
+
+    let solver = Solver::new(&pentominoes);
+    let to_set = |solution: &[usize]| {
+        solution
+            .iter()
+            .map(|index| pentominoes[*index].tile)
+            .collect()
+    };
+    let symmetries: [fn(&tile::Set<5>) -> tile::Set<5>; 7] = [
+        tile::Set::reflect_x,
+        tile::Set::reflect_y,
+        tile::Set::rotate_90,
+        tile::Set::rotate_180,
+        tile::Set::rotate_270,
+        |set| set.reflect_x().rotate_90(),
+        |set| set.reflect_x().rotate_270(),
+    ];
+
+    let (count, pruned) = solver.solve_count_canonical_incremental_with_stats(to_set, &symmetries);
+    let (_, full) = solver.solve_with_stats(|_| ControlFlow::<()>::Continue(()));
+
+    assert_eq!(count, 65);
+    assert_eq!(pruned.solutions, 65);
+    assert_eq!(
+        solver.solve_count_canonical_incremental(to_set, &symmetries),
+        65
+    );
+
+    // Non-canonical branches are cut off before reaching their leaves
+    assert!(
+        pruned.nodes < full.nodes,
+        "{} >= {}",
+        pruned.nodes,
+        full.nodes
+    );
+}
+
+#[test]
+fn corners_3x20() {
+    let pentominoes = pack(3, 20, |_| true);
+    let tiles = pentominoes
+        .iter()
+        .map(|pentomino| (pentomino.encode_id() as usize, pentomino.tile))
+        .collect::<Vec<_>>();
+
+    let counts = Solver::new(&pentominoes).solve_count_by_corners(&tiles, 3, 20);
+    let u = Some((b'U' - b'O') as usize);
+    let v = Some((b'V' - b'O') as usize);
+
+    // Both solutions, in all four orientations, have the U and V at opposite ends
+    assert_eq!(
+        counts.into_iter().collect::<Vec<_>>(),
+        [([u, v, u, v], 4), ([v, u, v, u], 4)],
+    );
+
+    // Degenerate boards have no corners to key by
+    let solver = Solver::new(&pentominoes);
+    assert!(solver.solve_count_by_corners(&tiles, 0, 20).is_empty());
+    assert!(solver.solve_count_by_corners(&tiles, 3, 0).is_empty());
+}
+
+#[test]
+fn placements_3x20() {
+    let pentominoes = pack(3, 20, |_| true);
+    let tiles = pentominoes
+        .iter()
+        .map(|pentomino| (pentomino.encode_id() as usize, pentomino.tile))
+        .collect::<Vec<_>>();
+
+    let mut count = 0;
+    Solver::new(&pentominoes).solve_placements(&tiles, |placements| {
+        let mut grid = [[None; 20]; 3];
+
+        for (id, tile) in placements {
+            for point in tile.as_ref() {
+                let cell = &mut grid[point.i as usize][point.j as usize];
+                assert_eq!(*cell, None);
+                *cell = Some(*id);
+            }
+        }
+
+        assert!(grid.iter().flatten().all(Option::is_some));
+        assert_eq!(
+            placements
+                .iter()
+                .map(|(id, _)| *id)
+                .collect::<BTreeSet<_>>(),
+            (0..12).collect(),
+        );
+
+        count += 1;
+        ControlFlow::<(), _>::Continue(())
+    });
+
+    assert_eq!(count, 8);
+}
+
+fn rectangle(rows: u16, cols: u16) -> BTreeSet<tile::Set<5>> {
     solve(rows, cols, |_| true)
 }
 
-fn solve<F: FnMut(Point) -> bool>(rows: u8, cols: u8, filter: F) -> BTreeSet<tile::Set<5>> {
+fn solve<F: FnMut(Point) -> bool>(rows: u16, cols: u16, filter: F) -> BTreeSet<tile::Set<5>> {
     let pentominoes = pack(rows, cols, filter);
 
     let mut count = 0;
@@ -181,10 +971,10 @@ fn solve<F: FnMut(Point) -> bool>(rows: u8, cols: u8, filter: F) -> BTreeSet<til
 
     let solver = Solver::new(&pentominoes);
 
-    solver.solve(|solution| {
+    solver.solve_mapped(&pentominoes, |solution| {
         let tiles = solution
             .iter()
-            .map(|index| pentominoes[*index].tile)
+            .map(|pentomino| pentomino.tile)
             .collect::<tile::Set<5>>()
             .canonicalize();
 
@@ -199,60 +989,38 @@ fn solve<F: FnMut(Point) -> bool>(rows: u8, cols: u8, filter: F) -> BTreeSet<til
     seen
 }
 
-fn pack<F: FnMut(Point) -> bool>(rows: u8, cols: u8, mut filter: F) -> Vec<Pentomino> {
-    let mut pentominoes = Vec::new();
-
-    for pentomino in transformations().into_iter() {
-        for row in 0..rows {
-            'outer: for col in 0..cols {
-                let mut translated = pentomino;
-
-                for (before, after) in pentomino.tile.as_ref().iter().zip(translated.tile.as_mut())
-                {
-                    let point = Point {
-                        i: before.i + row,
-                        j: before.j + col,
-                    };
-
-                    if point.i >= rows || point.j >= cols || !filter(point) {
-                        continue 'outer;
-                    }
-
-                    *after = point;
-                }
-
-                pentominoes.push(translated);
-            }
-        }
+/// Return a deterministic stream of indices below each requested bound.
+fn indices(seed: u64) -> impl FnMut(usize) -> usize {
+    // PCG's multiplier and increment, keeping only the better-mixed high bits
+    let mut state = seed;
+    move |bound| {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        ((state >> 33) % bound as u64) as usize
     }
+}
 
-    pentominoes
+/// Return the statistics of a full search.
+fn stats(solver: &Solver) -> solve::Stats {
+    solver
+        .solve_with_stats(|_| ControlFlow::<(), _>::Continue(()))
+        .1
 }
 
-fn transformations() -> BTreeSet<Pentomino> {
-    PENTOMINOES
-        .iter()
-        .flat_map(|&Pentomino { tile, id }| {
-            tile.transformations()
-                .map(move |tile| Pentomino { id, tile })
+fn pack<F: FnMut(Point) -> bool>(rows: u16, cols: u16, filter: F) -> Vec<Pentomino> {
+    let tiles = PENTOMINOES.map(|pentomino| pentomino.tile);
+    packing::place_all(&tiles, rows, cols, filter)
+        .into_iter()
+        .map(|(index, tile)| Pentomino {
+            id: PENTOMINOES[index].id,
+            tile,
         })
         .collect()
 }
 
 #[expect(unused)]
-fn debug(rows: u8, cols: u8, set: &tile::Set<5>) {
-    let mut grid = HashMap::new();
-
-    for (i, tile) in set.iter().enumerate() {
-        for point in tile.as_ref() {
-            grid.insert(point, i);
-        }
-    }
-
-    for i in 0..rows {
-        for j in 0..cols {
-            eprint!("\x1b[48;5;{}m ", grid[&Point { i, j }]);
-        }
-        eprintln!("\x1b[49m");
-    }
+fn debug(set: &IdSet<char, 5>) {
+    let (rows, cols) = set.bounding_box();
+    eprint!("{}", set.render(rows, cols));
 }
@@ -0,0 +1,41 @@
+use std::collections::BTreeSet;
+
+use dancing_links::tile;
+
+#[test]
+fn counts() {
+    // https://oeis.org/A000105
+    assert_eq!(tile::polyominoes::<1>().len(), 1);
+    assert_eq!(tile::polyominoes::<2>().len(), 1);
+    assert_eq!(tile::polyominoes::<3>().len(), 2);
+    assert_eq!(tile::polyominoes::<4>().len(), 5);
+    assert_eq!(tile::polyominoes::<5>().len(), 12);
+    assert_eq!(tile::polyominoes::<6>().len(), 35);
+    assert_eq!(tile::polyominoes::<7>().len(), 108);
+}
+
+#[test]
+fn pentominoes() {
+    let canonical = tile::PENTOMINOES
+        .iter()
+        .map(|tile| tile.transformations().min().unwrap())
+        .collect::<BTreeSet<_>>();
+
+    assert_eq!(tile::polyominoes::<5>(), canonical);
+}
+
+#[test]
+fn from_points() {
+    use dancing_links::tile::Point;
+    use dancing_links::tile::TileError;
+    use dancing_links::Tile;
+
+    assert_eq!(
+        Tile::from_points([Point { i: 0, j: 0 }, Point { i: 0, j: 0 }]),
+        Err(TileError::Duplicate),
+    );
+
+    let points = [Point { i: 0, j: 1 }, Point { i: 0, j: 0 }];
+    assert_eq!(Tile::from_points(points), Ok(Tile::new(points)));
+    assert_eq!(Tile::<0>::from_points([]), Ok(Tile::new([])));
+}
@@ -0,0 +1,41 @@
+#![cfg(feature = "serde")]
+
+use dancing_links::tile;
+use dancing_links::tile::Point;
+use dancing_links::Tile;
+
+#[test]
+fn round_trip() {
+    for tile in tile::PENTOMINOES {
+        let json = serde_json::to_string(&tile).unwrap();
+        assert_eq!(serde_json::from_str::<Tile<5>>(&json).unwrap(), tile);
+    }
+
+    let set = tile::PENTOMINOES.into_iter().collect::<tile::Set<5>>();
+    let json = serde_json::to_string(&set).unwrap();
+    assert_eq!(serde_json::from_str::<tile::Set<5>>(&json).unwrap(), set);
+}
+
+#[test]
+fn reordered() {
+    let tile = serde_json::from_str::<Tile<3>>(
+        r#"[{"i": 0, "j": 2}, {"i": 0, "j": 0}, {"i": 0, "j": 1}]"#,
+    )
+    .unwrap();
+    assert_eq!(tile, dancing_links::tile!(3 X X X));
+
+    let set = serde_json::from_str::<tile::Set<1>>(r#"[[{"i": 1, "j": 0}], [{"i": 0, "j": 0}]]"#)
+        .unwrap();
+    assert_eq!(
+        set.iter().collect::<Vec<_>>(),
+        [
+            &Tile::new([Point { i: 0, j: 0 }]),
+            &Tile::new([Point { i: 1, j: 0 }]),
+        ],
+    );
+}
+
+#[test]
+fn length_mismatch() {
+    assert!(serde_json::from_str::<Tile<2>>(r#"[{"i": 0, "j": 0}]"#).is_err());
+}
@@ -0,0 +1,61 @@
+use dancing_links::solve::SolverBuilder;
+use dancing_links::tile3;
+use dancing_links::tile3::Point3;
+use dancing_links::Tile3;
+
+const SIZE: u16 = 3;
+
+/// Add a row for every placement of every rotation of `tile` within
+/// the cube, covering its cells and the column for piece `id`.
+fn place<const LEN: usize>(builder: &mut SolverBuilder, id: u16, tile: Tile3<LEN>) {
+    for rotation in tile.rotations() {
+        let (rows, cols, layers) = rotation.dimensions();
+        for di in 0..=SIZE - rows {
+            for dj in 0..=SIZE - cols {
+                for dk in 0..=SIZE - layers {
+                    builder.add_row(
+                        rotation
+                            .as_ref()
+                            .iter()
+                            .map(|Point3 { i, j, k }| ((i + di) * SIZE + j + dj) * SIZE + k + dk)
+                            .chain(core::iter::once(SIZE * SIZE * SIZE + id))
+                            .map(u32::from),
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn orientations() {
+    let [l, t, s, left, right, branch] = tile3::SOMA_TETRACUBES;
+
+    assert_eq!(tile3::SOMA_V.rotations().count(), 12);
+    assert_eq!(l.rotations().count(), 24);
+    assert_eq!(t.rotations().count(), 12);
+    assert_eq!(s.rotations().count(), 12);
+    assert_eq!(left.rotations().count(), 12);
+    assert_eq!(branch.rotations().count(), 8);
+
+    // Planar pieces are their own mirror images
+    assert_eq!(l.transformations().count(), 24);
+    assert_eq!(branch.transformations().count(), 8);
+
+    // Screws are mirror images of each other
+    assert_eq!(left.transformations().count(), 24);
+    assert!(left.transformations().any(|tile| tile == right));
+    assert!(!left.rotations().any(|tile| tile == right));
+}
+
+#[test]
+fn soma() {
+    let mut builder = SolverBuilder::new();
+    place(&mut builder, 0, tile3::SOMA_V);
+    for (id, tile) in tile3::SOMA_TETRACUBES.into_iter().enumerate() {
+        place(&mut builder, id as u16 + 1, tile);
+    }
+
+    // 240 solutions up to the 48 symmetries of the cube
+    assert_eq!(builder.build().solve_count(), 240 * 48);
+}
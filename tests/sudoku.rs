@@ -0,0 +1,76 @@
+#![cfg(feature = "sudoku")]
+
+use dancing_links::sudoku::InvalidDigit;
+use dancing_links::sudoku::ParseSudokuError;
+use dancing_links::sudoku::Sudoku;
+
+// https://www.telegraph.co.uk/news/science/science-news/9359579/Worlds-hardest-sudoku-can-you-crack-it.html
+const HARDEST: &str = "
+    8........
+    ..36.....
+    .7..9.2..
+    .5...7...
+    ....457..
+    ...1...3.
+    ..1....68
+    ..85...1.
+    .9....4..
+";
+
+#[test]
+fn hardest() {
+    let sudoku = HARDEST.parse::<Sudoku>().unwrap();
+
+    assert_eq!(
+        sudoku.solve(),
+        Some([
+            [8, 1, 2, 7, 5, 3, 6, 4, 9],
+            [9, 4, 3, 6, 8, 2, 1, 7, 5],
+            [6, 7, 5, 4, 9, 1, 2, 8, 3],
+            [1, 5, 4, 2, 3, 7, 8, 9, 6],
+            [3, 6, 9, 8, 4, 5, 7, 2, 1],
+            [2, 8, 7, 1, 6, 9, 5, 3, 4],
+            [5, 2, 1, 9, 7, 4, 3, 6, 8],
+            [4, 3, 8, 5, 2, 6, 9, 1, 7],
+            [7, 9, 6, 3, 1, 8, 4, 5, 2],
+        ]),
+    );
+
+    assert_eq!(sudoku.count_solutions(), 1);
+}
+
+#[test]
+fn conflict() {
+    let sudoku = format!("11{}", ".".repeat(79)).parse::<Sudoku>().unwrap();
+    assert_eq!(sudoku.solve(), None);
+    assert_eq!(sudoku.count_solutions(), 0);
+}
+
+#[test]
+fn parse() {
+    assert_eq!(
+        "123".parse::<Sudoku>().err(),
+        Some(ParseSudokuError::Length(3))
+    );
+    assert_eq!(
+        "x".parse::<Sudoku>().err(),
+        Some(ParseSudokuError::InvalidCell('x'))
+    );
+}
+
+#[test]
+fn invalid_digit() {
+    let mut grid = [[0; 9]; 9];
+    grid[2][7] = 10;
+    assert_eq!(
+        Sudoku::new(grid).err(),
+        Some(InvalidDigit {
+            i: 2,
+            j: 7,
+            digit: 10
+        })
+    );
+
+    grid[2][7] = 9;
+    assert!(Sudoku::new(grid).is_ok());
+}
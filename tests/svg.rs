@@ -0,0 +1,51 @@
+#![cfg(feature = "svg")]
+
+use core::ops::ControlFlow;
+
+use dancing_links::packing;
+use dancing_links::solve::Row;
+use dancing_links::solve::Solver;
+use dancing_links::tile;
+use dancing_links::Tile;
+
+struct Placement(usize, Tile<5>);
+
+impl Row for Placement {
+    fn iter(&self) -> impl Iterator<Item = u32> {
+        self.1
+            .cells()
+            .map(|point| u32::from(point.i) * 20 + u32::from(point.j))
+            .chain(core::iter::once(60 + self.0 as u32))
+    }
+}
+
+#[test]
+fn rectangle_3x20() {
+    let placements = packing::place_all(&tile::PENTOMINOES, 3, 20, |_| true)
+        .into_iter()
+        .map(|(id, tile)| Placement(id, tile))
+        .collect::<Vec<_>>();
+
+    let set = Solver::new(&placements)
+        .solve_mapped(&placements, |solution| {
+            ControlFlow::Break(
+                solution
+                    .iter()
+                    .map(|placement| placement.1)
+                    .collect::<tile::Set<5>>(),
+            )
+        })
+        .unwrap();
+
+    let svg = set.to_svg(10);
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="30">"#));
+    assert!(svg.ends_with("</svg>\n"));
+    assert_eq!(svg.matches("<rect ").count(), 60);
+
+    // One color per tile
+    let colors = svg
+        .lines()
+        .filter_map(|line| line.split("fill=").nth(1))
+        .collect::<std::collections::BTreeSet<_>>();
+    assert_eq!(colors.len(), 12);
+}
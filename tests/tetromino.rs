@@ -0,0 +1,12 @@
+use dancing_links::tile;
+
+#[test]
+fn transform() {
+    // https://en.wikipedia.org/wiki/Tetromino#Free_tetrominoes
+    let expected = [2, 1, 4, 4, 8];
+
+    let actual = tile::TETROMINOES.map(|tile| tile.transformations().count());
+
+    assert_eq!(expected, actual);
+    assert_eq!(actual.iter().sum::<usize>(), 19);
+}
@@ -1,8 +1,15 @@
+use core::ops::ControlFlow;
 use std::collections::BTreeSet;
 
+use dancing_links::heuristic::FirstColumn;
+use dancing_links::heuristic::Heuristic;
+use dancing_links::heuristic::MinRemaining;
+use dancing_links::packing;
 use dancing_links::solve::Row;
+use dancing_links::solve::Search;
+use dancing_links::solve::Solver;
+use dancing_links::solve::StepResult;
 use dancing_links::tile;
-use dancing_links::tile::Point;
 use dancing_links::Tile;
 
 #[derive(Clone, Debug, PartialOrd, Ord, PartialEq, Eq)]
@@ -28,55 +35,154 @@ const TRIOMINOES: [Triomino; 2] = [
 ];
 
 impl Row for Triomino {
-    fn iter(&self) -> impl Iterator<Item = u16> {
+    fn iter(&self) -> impl Iterator<Item = u32> {
         self.0
-            .as_ref()
-            .iter()
+            .cells()
             // Imposes maximum width of 64 units
-            .map(|point| point.i as u16 * 64 + point.j as u16)
+            .map(|point| u32::from(point.i) * 64 + u32::from(point.j))
     }
 }
 
 /// Return number of ways to tile `rows` by `cols` rectangular
 /// grid using triominoes.
-fn solutions(rows: u8, cols: u8) -> usize {
-    use dancing_links::solve::Solver;
+fn solutions(rows: u16, cols: u16) -> usize {
+    Solver::new(&pack(rows, cols)).solve_count()
+}
+
+/// Return every placement of every triomino orientation
+/// within a `rows` by `cols` rectangular grid.
+fn pack(rows: u16, cols: u16) -> Vec<Triomino> {
+    let tiles = TRIOMINOES.map(|triomino| triomino.0);
+    packing::place_all(&tiles, rows, cols, |_| true)
+        .into_iter()
+        .map(|(_, tile)| Triomino(tile))
+        .collect()
+}
+
+#[test]
+fn rectangle_2x9() {
+    assert_eq!(solutions(2, 9), 41);
+}
 
-    let unique = TRIOMINOES
+#[test]
+fn optimize_2x3() {
+    let triominoes = pack(2, 3);
+    let tiles = triominoes
         .iter()
-        .flat_map(|triomino| triomino.0.transformations())
-        .map(Triomino)
-        .collect::<BTreeSet<_>>();
+        .map(|triomino| triomino.0)
+        .collect::<Vec<_>>();
 
-    let mut triominoes = Vec::new();
+    let solver = Solver::new(&triominoes);
+    assert_eq!(solver.solve_count(), 3);
 
-    for triomino in unique.iter() {
-        for row in 0..rows {
-            'outer: for col in 0..cols {
-                let mut translated = triomino.clone();
+    // Prefer solutions using straight pieces
+    let best = solver
+        .solve_optimizing(&tiles, |set| {
+            -(set
+                .iter()
+                .filter(|tile| tile.cells().all(|point| point.i == tile.as_ref()[0].i))
+                .count() as i64)
+        })
+        .unwrap();
 
-                for (before, after) in triomino.0.as_ref().iter().zip(translated.0.as_mut()) {
-                    let point = Point {
-                        i: before.i + row,
-                        j: before.j + col,
-                    };
+    assert_eq!(
+        best,
+        [tile!(3 X X X), tile!(3 . . . X X X)]
+            .into_iter()
+            .collect::<tile::Set<3>>(),
+    );
+}
 
-                    if point.i >= rows || point.j >= cols {
-                        continue 'outer;
-                    }
+#[test]
+fn heuristic() {
+    fn count<H: Heuristic>(solver: &Solver, heuristic: &H) -> usize {
+        let mut count = 0;
+        solver.solve_with_heuristic(heuristic, |_| {
+            count += 1;
+            ControlFlow::<(), _>::Continue(())
+        });
+        count
+    }
 
-                    *after = point;
-                }
+    let solver = Solver::new(&pack(2, 9));
+    assert_eq!(count(&solver, &FirstColumn), 41);
+    assert_eq!(count(&solver, &MinRemaining), 41);
+}
 
-                triominoes.push(translated);
+#[test]
+fn search() {
+    fn drain(search: &mut Search) -> Vec<Vec<usize>> {
+        let mut solutions = Vec::new();
+        loop {
+            match search.step() {
+                StepResult::Solution(mut rows) => {
+                    rows.sort();
+                    solutions.push(rows);
+                }
+                StepResult::Done => return solutions,
+                StepResult::Select(_) | StepResult::Backtrack => (),
             }
         }
     }
 
-    Solver::new(&triominoes).solve_count()
+    let solver = Solver::new(&pack(2, 9));
+    let mut search = Search::new(solver.clone());
+    let all = drain(&mut search);
+    assert_eq!(all.len(), 41);
+
+    // Interrupt partway, then resume from a fresh solver
+    let mut search = Search::new(solver.clone());
+    let mut before = Vec::new();
+    while before.len() < 10 {
+        if let StepResult::Solution(mut rows) = search.step() {
+            rows.sort();
+            before.push(rows);
+        }
+    }
+
+    let state = search.save();
+    let mut resumed = Search::restore(solver.clone(), &state).unwrap();
+    assert_eq!(resumed.save(), state);
+
+    before.extend(drain(&mut resumed));
+    assert_eq!(before, all);
+
+    // Restored matrix matches the original
+    assert_eq!(resumed.into_solver().solve_count(), 41);
+    assert_eq!(search.into_solver().solve_count(), 41);
 }
 
 #[test]
-fn rectangle_2x9() {
-    assert_eq!(solutions(2, 9), 41);
+fn canonical() {
+    let triominoes = pack(2, 9);
+    let solver = Solver::new(&triominoes);
+    let to_set = |rows: &[usize]| {
+        rows.iter()
+            .map(|row| triominoes[*row].0)
+            .collect::<tile::Set<3>>()
+    };
+
+    let mut expected = BTreeSet::new();
+    solver.solve(|rows| {
+        expected.insert(to_set(rows).canonicalize());
+        ControlFlow::<(), _>::Continue(())
+    });
+
+    let count = solver.solve_count_canonical(|rows| to_set(rows).canonicalize());
+    assert_eq!(count, expected.len());
+    assert!(count < 41);
+    assert_eq!(
+        solver.solve_canonical(|rows| to_set(rows).canonicalize()),
+        expected
+    );
+
+    // Identity key counts every solution
+    assert_eq!(
+        solver.solve_count_canonical(|rows| {
+            let mut rows = rows.to_vec();
+            rows.sort();
+            rows
+        }),
+        41,
+    );
 }